@@ -0,0 +1,40 @@
+//!Synthetic scans for the benchmarks.
+use std::fmt::Write;
+
+///Returns Nmap XML output for a version scan of `hosts` hosts with
+///`ports` open ports each.
+pub fn synthetic_scan(hosts: usize, ports: usize) -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <nmaprun scanner=\"nmap\" args=\"nmap -sV -oX - 10.0.0.0/8\" start=\"1588318812\" version=\"7.80\" xmloutputversion=\"1.04\">\n",
+    );
+
+    for h in 0..hosts {
+        let _ = write!(
+            xml,
+            "<host starttime=\"1588318812\" endtime=\"1588318912\"><status state=\"up\" reason=\"echo-reply\" reason_ttl=\"53\"/>\n\
+             <address addr=\"10.{}.{}.{}\" addrtype=\"ipv4\"/>\n\
+             <hostnames><hostname name=\"host-{}.example\" type=\"PTR\"/></hostnames>\n\
+             <ports><extraports state=\"closed\" count=\"{}\"><extrareasons reason=\"resets\" count=\"{}\"/></extraports>\n",
+            (h >> 16) & 0xff,
+            (h >> 8) & 0xff,
+            h & 0xff,
+            h,
+            1000 - ports.min(1000),
+            1000 - ports.min(1000)
+        );
+        for p in 0..ports {
+            let _ = writeln!(
+                xml,
+                "<port protocol=\"tcp\" portid=\"{}\"><state state=\"open\" reason=\"syn-ack\" reason_ttl=\"53\"/>\
+                 <service name=\"http\" product=\"nginx\" version=\"1.18.0\" extrainfo=\"Ubuntu\" method=\"probed\" conf=\"10\"><cpe>cpe:/a:igor_sysoev:nginx:1.18.0</cpe></service>\
+                 <script id=\"http-title\" output=\"Welcome to nginx!\"><elem key=\"title\">Welcome to nginx!</elem></script></port>",
+                8000 + p
+            );
+        }
+        xml.push_str("</ports>\n<times srtt=\"5263\" rttvar=\"4662\" to=\"100000\"/>\n</host>\n");
+    }
+
+    xml.push_str("<runstats><finished time=\"1588318912\"/></runstats>\n</nmaprun>\n");
+    xml
+}
@@ -0,0 +1,30 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use nmap_xml_parser::NmapResults;
+
+mod common;
+
+fn sequential_vs_parallel(c: &mut Criterion) {
+    let inputs = vec![
+        ("10000x2", common::synthetic_scan(10_000, 2)),
+        ("100000x2", common::synthetic_scan(100_000, 2)),
+    ];
+
+    let mut group = c.benchmark_group("hosts");
+    for (name, xml) in &inputs {
+        group.throughput(Throughput::Bytes(xml.len() as u64));
+        group.bench_with_input(BenchmarkId::new("parse", name), xml, |b, xml| {
+            b.iter(|| NmapResults::parse(xml).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("par_parse", name), xml, |b, xml| {
+            b.iter(|| NmapResults::par_parse(xml).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = sequential_vs_parallel
+}
+criterion_main!(benches);
@@ -0,0 +1,36 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use nmap_xml_parser::{NmapResults, NmapResultsRef};
+use std::fs;
+use std::path::PathBuf;
+
+mod common;
+
+fn owned_vs_borrowed(c: &mut Criterion) {
+    let mut path = PathBuf::new();
+    path.push(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    path.push("tests/scanme-version.xml");
+
+    let inputs = vec![
+        ("scanme-version", fs::read_to_string(path).unwrap()),
+        ("1000x100", common::synthetic_scan(1000, 100)),
+    ];
+
+    let mut group = c.benchmark_group("parse");
+    for (name, xml) in &inputs {
+        group.throughput(Throughput::Bytes(xml.len() as u64));
+        group.bench_with_input(BenchmarkId::new("owned", name), xml, |b, xml| {
+            b.iter(|| NmapResults::parse(xml).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("borrowed", name), xml, |b, xml| {
+            b.iter(|| NmapResultsRef::parse(xml).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = owned_vs_borrowed
+}
+criterion_main!(benches);
@@ -0,0 +1,778 @@
+//!The command line Nmap was run with, from the `args` attribute.
+//!
+//!```
+//!use nmap_xml_parser::args::{ScanOptions, ScanType, TimingTemplate};
+//!
+//!let options = ScanOptions::parse("nmap -sS -Pn -T5 -p22,80 --script vuln -oA test scanme.nmap.org");
+//!assert_eq!(options.scan_types(), vec![ScanType::Syn]);
+//!assert_eq!(options.port_spec(), Some("22,80"));
+//!assert_eq!(options.timing_template(), Some(TimingTemplate::Insane));
+//!assert!(options.skips_host_discovery());
+//!assert!(options.uses_script_category("vuln"));
+//!assert_eq!(options.targets, vec!["scanme.nmap.org"]);
+//!```
+use std::fmt;
+use strum_macros::{Display, EnumString};
+
+///Long options that take a value, without their leading dashes. Nmap also
+///accepts these with a single dash.
+const LONG_WITH_VALUE: &[&str] = &[
+    "data",
+    "data-length",
+    "data-string",
+    "datadir",
+    "dns-servers",
+    "exclude",
+    "exclude-ports",
+    "excludefile",
+    "host-timeout",
+    "iL",
+    "iR",
+    "initial-rtt-timeout",
+    "ip-options",
+    "max-hostgroup",
+    "max-os-tries",
+    "max-parallelism",
+    "max-rate",
+    "max-retries",
+    "max-rtt-timeout",
+    "max-scan-delay",
+    "min-hostgroup",
+    "min-parallelism",
+    "min-rate",
+    "min-rtt-timeout",
+    "mtu",
+    "nsock-engine",
+    "oA",
+    "oG",
+    "oH",
+    "oM",
+    "oN",
+    "oS",
+    "oX",
+    "port-ratio",
+    "proxies",
+    "proxy",
+    "resume",
+    "scan-delay",
+    "scanflags",
+    "script",
+    "script-args",
+    "script-args-file",
+    "script-help",
+    "script-timeout",
+    "servicedb",
+    "sI",
+    "source-port",
+    "spoof-mac",
+    "stats-every",
+    "stylesheet",
+    "top-ports",
+    "ttl",
+    "version-intensity",
+    "versiondb",
+];
+
+///Long options that take no value and could be mistaken for short options
+///when given with a single dash, e.g. `-send-eth`.
+const LONG_FLAGS: &[&str] = &[
+    "script-trace",
+    "script-updatedb",
+    "send-eth",
+    "send-ip",
+    "system-dns",
+];
+
+///Short options that take a value, either attached (`-p80`) or as the next
+///argument (`-p 80`).
+const SHORT_WITH_VALUE: &[char] = &['b', 'D', 'e', 'g', 'M', 'm', 'p', 'S', 'T'];
+
+///The command line of a scan, split into options and targets.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScanOptions {
+    ///The program that was run, usually `nmap`.
+    pub program: String,
+    ///The options, in the order they were given.
+    pub options: Vec<ScanOption>,
+    ///The targets given on the command line, e.g. host names, addresses and
+    ///networks.
+    pub targets: Vec<String>,
+}
+
+///One option on the command line, e.g. `-p 22,80`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScanOption {
+    ///Name of the option. Long options are named with two dashes (e.g.
+    ///`--script`), except for the ones Nmap documents with one (e.g. `-oX`
+    ///and `-iL`). Scan types and host discovery probes are named with their
+    ///letters (e.g. `-sS` and `-PS`).
+    pub name: String,
+    ///Value of the option, e.g. `22,80` for `-p22,80`.
+    pub value: Option<String>,
+    ///The arguments the option was given as, and the name and value they
+    ///were split into. They are only used while `name` and `value` are
+    ///unchanged.
+    args: Vec<String>,
+    given: (String, Option<String>),
+}
+
+impl ScanOption {
+    ///Creates an option that is given as `name` followed by `value`.
+    pub fn new(name: &str, value: Option<&str>) -> Self {
+        ScanOption::given(name.to_string(), value.map(String::from), Vec::new())
+    }
+
+    fn given(name: String, value: Option<String>, args: Vec<String>) -> Self {
+        ScanOption {
+            given: (name.clone(), value.clone()),
+            name,
+            value,
+            args,
+        }
+    }
+
+    ///Returns the arguments the option is given as: the original spelling,
+    ///e.g. `-p80`, if it still matches `name` and `value`, otherwise `name`
+    ///followed by `value`.
+    pub fn to_args(&self) -> Vec<String> {
+        if !self.args.is_empty() && self.given.0 == self.name && self.given.1 == self.value {
+            return self.args.clone();
+        }
+
+        let mut args = vec![self.name.clone()];
+        args.extend(self.value.clone());
+        args
+    }
+}
+
+impl ScanOptions {
+    ///Splits the command line `args`.
+    ///
+    ///Nmap does not quote arguments when it writes the `args` attribute, so
+    ///values containing spaces cannot be told apart from separate arguments.
+    ///Quotes that are present are removed.
+    pub fn parse(args: &str) -> Self {
+        let mut words = split(args).into_iter();
+        let program = words.next().unwrap_or_default();
+
+        let mut options = Vec::new();
+        let mut targets = Vec::new();
+        while let Some(word) = words.next() {
+            if !word.starts_with('-') || word == "-" {
+                targets.push(word);
+                continue;
+            }
+
+            let (name, value, takes_value) = classify(&word);
+            let mut args = vec![word];
+            let value = match value {
+                Some(v) => Some(v),
+                None if takes_value => {
+                    let v = words.next();
+                    args.extend(v.clone());
+                    v
+                }
+                None => None,
+            };
+            options.push(ScanOption::given(name, value, args));
+        }
+
+        ScanOptions {
+            program,
+            options,
+            targets,
+        }
+    }
+
+    ///Returns the value of the last option called `name`, which is how Nmap
+    ///treats repeated options.
+    pub fn option(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .find(|o| o.name == name)
+            .and_then(|o| o.value.as_deref())
+    }
+
+    ///Returns true if an option called `name` was given.
+    pub fn has(&self, name: &str) -> bool {
+        self.options.iter().any(|o| o.name == name)
+    }
+
+    ///Removes every option whose name is in `names`.
+    pub fn remove(&mut self, names: &[&str]) {
+        self.options.retain(|o| !names.contains(&o.name.as_str()));
+    }
+
+    ///Returns the scan techniques selected with `-s` and `-b`, in the order
+    ///they were given. Nmap scans with [`ScanType::Syn`](enum.ScanType.html)
+    ///or [`ScanType::Connect`](enum.ScanType.html) if none is given.
+    pub fn scan_types(&self) -> Vec<ScanType> {
+        let mut r = Vec::new();
+        for option in &self.options {
+            let letters = if option.name == "-b" {
+                "b"
+            } else if option.name.starts_with("-s") {
+                &option.name[2..]
+            } else {
+                continue;
+            };
+            for scan_type in letters.chars().filter_map(ScanType::from_letter) {
+                if !r.contains(&scan_type) {
+                    r.push(scan_type);
+                }
+            }
+        }
+        r
+    }
+
+    ///Returns true if service and version detection was enabled with `-sV`
+    ///or `-A`.
+    pub fn version_detection(&self) -> bool {
+        self.has("-A") || self.scan_letters().any(|c| c == 'V' || c == 'R')
+    }
+
+    ///Returns true if OS detection was enabled with `-O` or `-A`.
+    pub fn os_detection(&self) -> bool {
+        self.has("-A") || self.has("-O")
+    }
+
+    ///Returns true if host discovery was skipped with `-Pn`, or its older
+    ///spellings `-PN` and `-P0`.
+    pub fn skips_host_discovery(&self) -> bool {
+        self.options
+            .iter()
+            .any(|o| o.name == "-Pn" || o.name == "-PN" || o.name == "-P0")
+    }
+
+    ///Returns the ports given with `-p`, e.g. `T:22,80,U:53`.
+    pub fn port_spec(&self) -> Option<&str> {
+        self.option("-p")
+    }
+
+    ///Returns the timing template given with `-T`. Nmap uses
+    ///[`TimingTemplate::Normal`](enum.TimingTemplate.html) if none is
+    ///given. Values Nmap does not accept are ignored.
+    pub fn timing_template(&self) -> Option<TimingTemplate> {
+        self.option("-T")
+            .and_then(|t| t.to_ascii_lowercase().parse().ok())
+    }
+
+    ///Returns the script selections given with `--script`, e.g. `vuln` and
+    ///`http-*`, in the order they were given. The default scripts selected
+    ///by `-sC` and `-A` are not included.
+    pub fn scripts(&self) -> Vec<&str> {
+        self.values("--script")
+            .flat_map(|v| v.split(','))
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .collect()
+    }
+
+    ///Returns true if the scripts of `category`, e.g. `intrusive`, were
+    ///selected by name or, for the `default` category, with `-sC` or `-A`.
+    ///
+    ///Categories are matched in the words of each selection, so that e.g.
+    ///`default or intrusive` selects `intrusive` and `not intrusive` does
+    ///not. Scripts selected by their own name are not taken into account,
+    ///as their categories are not recorded in the output.
+    pub fn uses_script_category(&self, category: &str) -> bool {
+        if category.eq_ignore_ascii_case("default")
+            && (self.has("-A") || self.scan_letters().any(|c| c == 'C'))
+        {
+            return true;
+        }
+
+        self.scripts().iter().any(|selection| {
+            let words = selection
+                .split(|c: char| c.is_whitespace() || c == '(' || c == ')')
+                .filter(|w| !w.is_empty())
+                .collect::<Vec<_>>();
+            words.iter().enumerate().any(|(i, w)| {
+                w.eq_ignore_ascii_case(category)
+                    && (i == 0 || !words[i - 1].eq_ignore_ascii_case("not"))
+            })
+        })
+    }
+
+    ///Returns the arguments given with `--script-args`, as names and
+    ///values. Values are returned as given, so tables such as
+    ///`{a,b}` are not split.
+    pub fn script_args(&self) -> Vec<(&str, Option<&str>)> {
+        let mut r = Vec::new();
+        for value in self.values("--script-args") {
+            for arg in split_script_args(value) {
+                match arg.find('=') {
+                    Some(i) => r.push((arg[..i].trim(), Some(arg[i + 1..].trim()))),
+                    None => r.push((arg.trim(), None)),
+                }
+            }
+        }
+        r
+    }
+
+    ///Returns the output files given with `-oN`, `-oX`, `-oS`, `-oG` and
+    ///`-oA`, in the order they were given. A path of `-` is standard
+    ///output.
+    pub fn outputs(&self) -> Vec<Output> {
+        self.options
+            .iter()
+            .filter_map(|o| {
+                let format = OutputFormat::from_option(&o.name)?;
+                let path = o.value.clone()?;
+                Some(Output { format, path })
+            })
+            .collect()
+    }
+
+    ///Returns the values of every option called `name`.
+    fn values<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.options
+            .iter()
+            .filter(move |o| o.name == name)
+            .filter_map(|o| o.value.as_deref())
+    }
+
+    ///Returns the letters of the `-s` options.
+    fn scan_letters(&self) -> impl Iterator<Item = char> + '_ {
+        self.options
+            .iter()
+            .filter(|o| o.name.starts_with("-s"))
+            .flat_map(|o| o.name[2..].chars())
+    }
+
+    ///Returns the arguments of the command line, including the program.
+    pub fn to_args(&self) -> Vec<String> {
+        let mut r = vec![self.program.clone()];
+        for option in &self.options {
+            r.extend(option.to_args());
+        }
+        r.extend(self.targets.iter().cloned());
+        r
+    }
+}
+
+///Formats the command line so that it can be pasted into a POSIX shell.
+impl fmt::Display for ScanOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let args = self.to_args().iter().map(|a| quote(a)).collect::<Vec<_>>();
+        write!(f, "{}", args.join(" "))
+    }
+}
+
+///A scan technique, e.g. `-sS`. It is formatted as in the `type` attribute
+///of `scaninfo`, e.g. `syn`.
+#[derive(EnumString, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ScanType {
+    ///TCP SYN scan, `-sS`.
+    #[strum(serialize = "syn")]
+    Syn,
+    ///TCP connect scan, `-sT`.
+    #[strum(serialize = "connect")]
+    Connect,
+    ///TCP ACK scan, `-sA`.
+    #[strum(serialize = "ack")]
+    Ack,
+    ///TCP Window scan, `-sW`.
+    #[strum(serialize = "window")]
+    Window,
+    ///TCP Maimon scan, `-sM`.
+    #[strum(serialize = "maimon")]
+    Maimon,
+    ///UDP scan, `-sU`.
+    #[strum(serialize = "udp")]
+    Udp,
+    ///TCP Null scan, `-sN`.
+    #[strum(serialize = "null")]
+    Null,
+    ///TCP FIN scan, `-sF`.
+    #[strum(serialize = "fin")]
+    Fin,
+    ///TCP Xmas scan, `-sX`.
+    #[strum(serialize = "xmas")]
+    Xmas,
+    ///Idle scan, `-sI`.
+    #[strum(serialize = "idle")]
+    Idle,
+    ///SCTP INIT scan, `-sY`.
+    #[strum(serialize = "sctpinit")]
+    SctpInit,
+    ///SCTP COOKIE-ECHO scan, `-sZ`.
+    #[strum(serialize = "sctpcookieecho")]
+    SctpCookieEcho,
+    ///IP protocol scan, `-sO`.
+    #[strum(serialize = "ipproto")]
+    IpProtocol,
+    ///FTP bounce scan, `-b`.
+    #[strum(serialize = "bounce")]
+    FtpBounce,
+    ///List scan, `-sL`, which only lists the targets.
+    #[strum(serialize = "list")]
+    List,
+    ///Ping scan, `-sn` or `-sP`, which only discovers hosts.
+    #[strum(serialize = "ping")]
+    Ping,
+    ///Any other `type` value, kept verbatim, e.g. a scan type added in a
+    ///later version of Nmap.
+    #[strum(default = "true")]
+    Unknown(String),
+}
+
+impl fmt::Display for ScanType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            ScanType::Syn => "syn",
+            ScanType::Connect => "connect",
+            ScanType::Ack => "ack",
+            ScanType::Window => "window",
+            ScanType::Maimon => "maimon",
+            ScanType::Udp => "udp",
+            ScanType::Null => "null",
+            ScanType::Fin => "fin",
+            ScanType::Xmas => "xmas",
+            ScanType::Idle => "idle",
+            ScanType::SctpInit => "sctpinit",
+            ScanType::SctpCookieEcho => "sctpcookieecho",
+            ScanType::IpProtocol => "ipproto",
+            ScanType::FtpBounce => "bounce",
+            ScanType::List => "list",
+            ScanType::Ping => "ping",
+            ScanType::Unknown(s) => s,
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl ScanType {
+    fn from_letter(c: char) -> Option<Self> {
+        let r = match c {
+            'S' => ScanType::Syn,
+            'T' => ScanType::Connect,
+            'A' => ScanType::Ack,
+            'W' => ScanType::Window,
+            'M' => ScanType::Maimon,
+            'U' => ScanType::Udp,
+            'N' => ScanType::Null,
+            'F' => ScanType::Fin,
+            'X' => ScanType::Xmas,
+            'I' => ScanType::Idle,
+            'Y' => ScanType::SctpInit,
+            'Z' => ScanType::SctpCookieEcho,
+            'O' => ScanType::IpProtocol,
+            'b' => ScanType::FtpBounce,
+            'L' => ScanType::List,
+            'n' | 'P' => ScanType::Ping,
+            _ => return None,
+        };
+        Some(r)
+    }
+}
+
+///A timing template, `-T0` to `-T5`. Templates are ordered from slowest to
+///fastest.
+#[derive(EnumString, Display, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TimingTemplate {
+    #[strum(to_string = "paranoid", serialize = "0")]
+    Paranoid,
+    #[strum(to_string = "sneaky", serialize = "1")]
+    Sneaky,
+    #[strum(to_string = "polite", serialize = "2")]
+    Polite,
+    #[strum(to_string = "normal", serialize = "3")]
+    Normal,
+    #[strum(to_string = "aggressive", serialize = "4")]
+    Aggressive,
+    #[strum(to_string = "insane", serialize = "5")]
+    Insane,
+}
+
+///An output file, e.g. `-oX scan.xml`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Output {
+    pub format: OutputFormat,
+    ///Path of the file. For [`OutputFormat::All`](enum.OutputFormat.html)
+    ///this is the base name the extension of each format is added to.
+    pub path: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum OutputFormat {
+    ///Normal output, `-oN`.
+    Normal,
+    ///XML output, `-oX`.
+    Xml,
+    ///Script kiddie output, `-oS`.
+    ScriptKiddie,
+    ///Grepable output, `-oG` or its older spelling `-oM`.
+    Grepable,
+    ///Normal, XML and grepable output, `-oA`.
+    All,
+}
+
+impl OutputFormat {
+    fn from_option(name: &str) -> Option<Self> {
+        let r = match name {
+            "-oN" => OutputFormat::Normal,
+            "-oX" => OutputFormat::Xml,
+            "-oS" => OutputFormat::ScriptKiddie,
+            "-oG" | "-oM" => OutputFormat::Grepable,
+            "-oA" => OutputFormat::All,
+            _ => return None,
+        };
+        Some(r)
+    }
+}
+
+///Returns the name and attached value of the option `word`, and whether it
+///takes the next argument as its value if none is attached.
+fn classify(word: &str) -> (String, Option<String>, bool) {
+    let bare = word.trim_start_matches('-');
+    let (bare, attached) = match bare.find('=') {
+        Some(i) if word.starts_with("--") => (&bare[..i], Some(bare[i + 1..].to_string())),
+        _ => (bare, None),
+    };
+
+    if let Some(long) = LONG_WITH_VALUE.iter().find(|l| **l == bare) {
+        return (long_name(long), attached, true);
+    }
+    if word.starts_with("--") || attached.is_some() || LONG_FLAGS.contains(&bare) {
+        return (long_name(bare), attached, false);
+    }
+
+    let mut chars = bare.chars();
+    match (chars.next(), chars.as_str()) {
+        //Scan types, e.g. -sS and -sV.
+        (Some('s'), _) => (word.to_string(), None, false),
+        //Host discovery probes, e.g. -PS22 and -Pn.
+        (Some('P'), rest)
+            if rest.len() > 1 && rest.starts_with(|c: char| c.is_ascii_uppercase()) =>
+        {
+            let (probe, ports) = rest.split_at(1);
+            (format!("-P{}", probe), Some(ports.to_string()), false)
+        }
+        (Some('P'), _) => (word.to_string(), None, false),
+        (Some(c), "") if SHORT_WITH_VALUE.contains(&c) => (format!("-{}", c), None, true),
+        (Some(c), rest) if SHORT_WITH_VALUE.contains(&c) => {
+            (format!("-{}", c), Some(rest.to_string()), false)
+        }
+        _ => (word.to_string(), None, false),
+    }
+}
+
+fn long_name(name: &str) -> String {
+    //Options named like -oX and -iL are documented with a single dash.
+    if name.len() == 2 {
+        format!("-{}", name)
+    } else {
+        format!("--{}", name)
+    }
+}
+
+///Splits `s` on whitespace outside of single and double quotes.
+fn split(s: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = None;
+    let mut quote = None;
+
+    for c in s.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => word.get_or_insert_with(String::new).push(c),
+            (None, '\'') | (None, '"') => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => words.extend(word.take()),
+            (None, c) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+
+    words
+}
+
+///Splits the value of `--script-args` on commas outside of braces and
+///quotes.
+fn split_script_args(s: &str) -> Vec<&str> {
+    let mut r = Vec::new();
+    let mut depth = 0;
+    let mut quote = None;
+    let mut start = 0;
+
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'') | (None, '"') => quote = Some(c),
+            (None, '{') => depth += 1,
+            (None, '}') => depth -= 1,
+            (None, ',') if depth == 0 => {
+                r.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    r.push(&s[start..]);
+
+    r.into_iter().filter(|a| !a.trim().is_empty()).collect()
+}
+
+///Quotes `arg` for a POSIX shell if needed.
+fn quote(arg: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "-_./:,=@%+".contains(c);
+    if !arg.is_empty() && arg.chars().all(safe) {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn names(options: &ScanOptions) -> Vec<(&str, Option<&str>)> {
+        options
+            .options
+            .iter()
+            .map(|o| (o.name.as_str(), o.value.as_deref()))
+            .collect()
+    }
+
+    #[test]
+    fn options_and_targets() {
+        let options = ScanOptions::parse(
+            "nmap -sS -sV -Pn -PS22,80 -p 1-1000 -T4 -vv -oA out --script=default,vuln -script-args x=1 -iL hosts.txt 192.0.2.0/24 scanme.nmap.org",
+        );
+
+        assert_eq!(options.program, "nmap");
+        assert_eq!(
+            names(&options),
+            vec![
+                ("-sS", None),
+                ("-sV", None),
+                ("-Pn", None),
+                ("-PS", Some("22,80")),
+                ("-p", Some("1-1000")),
+                ("-T", Some("4")),
+                ("-vv", None),
+                ("-oA", Some("out")),
+                ("--script", Some("default,vuln")),
+                ("--script-args", Some("x=1")),
+                ("-iL", Some("hosts.txt")),
+            ]
+        );
+        assert_eq!(options.targets, vec!["192.0.2.0/24", "scanme.nmap.org"]);
+        assert_eq!(options.option("-T"), Some("4"));
+        assert!(options.has("-Pn"));
+    }
+
+    #[test]
+    fn typed_options() {
+        let options = ScanOptions::parse(
+            "nmap -sSUV -b ftp.example -O -T aggressive -send-eth --script \"(default or intrusive),http-*\" --script 'not brute' --script-args user=a,creds={x,y},flag -oX - -oG out.gnmap 192.0.2.1",
+        );
+
+        assert_eq!(
+            options.scan_types(),
+            vec![ScanType::Syn, ScanType::Udp, ScanType::FtpBounce]
+        );
+        assert!(options.version_detection());
+        assert!(options.os_detection());
+        assert!(!options.skips_host_discovery());
+        assert_eq!(options.timing_template(), Some(TimingTemplate::Aggressive));
+        assert!(options.has("--send-eth"));
+
+        assert_eq!(
+            options.scripts(),
+            vec!["(default or intrusive)", "http-*", "not brute"]
+        );
+        assert!(options.uses_script_category("intrusive"));
+        assert!(options.uses_script_category("default"));
+        assert!(!options.uses_script_category("brute"));
+        assert!(!options.uses_script_category("vuln"));
+
+        assert_eq!(
+            options.script_args(),
+            vec![
+                ("user", Some("a")),
+                ("creds", Some("{x,y}")),
+                ("flag", None)
+            ]
+        );
+        assert_eq!(
+            options.outputs(),
+            vec![
+                Output {
+                    format: OutputFormat::Xml,
+                    path: "-".to_string()
+                },
+                Output {
+                    format: OutputFormat::Grepable,
+                    path: "out.gnmap".to_string()
+                },
+            ]
+        );
+        assert_eq!(options.targets, vec!["192.0.2.1"]);
+    }
+
+    #[test]
+    fn defaults() {
+        let options = ScanOptions::parse("nmap -A -T5 -Pn scanme.nmap.org");
+
+        assert!(options.scan_types().is_empty());
+        assert!(options.version_detection());
+        assert!(options.os_detection());
+        assert!(options.skips_host_discovery());
+        assert!(options.uses_script_category("default"));
+        assert_eq!(options.timing_template(), Some(TimingTemplate::Insane));
+        assert!(TimingTemplate::Insane > TimingTemplate::Normal);
+        assert_eq!(ScanOptions::parse("nmap -T9 x").timing_template(), None);
+    }
+
+    #[test]
+    fn scan_type_names() {
+        assert_eq!("sctpinit".parse(), Ok(ScanType::SctpInit));
+        assert_eq!(ScanType::IpProtocol.to_string(), "ipproto");
+
+        let unknown = "synack".parse::<ScanType>().unwrap();
+        assert_eq!(unknown, ScanType::Unknown("synack".to_string()));
+        assert_eq!(unknown.to_string(), "synack");
+    }
+
+    #[test]
+    fn round_trip() {
+        let args = "nmap -p80 -T 4 --script=http-title -oX - 192.0.2.1";
+        let options = ScanOptions::parse(args);
+
+        assert_eq!(options.to_args().join(" "), args);
+        assert_eq!(options.to_string(), args);
+    }
+
+    #[test]
+    fn quoting() {
+        let mut options = ScanOptions::parse("nmap --script-args 'http.useragent=A B' x");
+        assert_eq!(options.option("--script-args"), Some("http.useragent=A B"));
+
+        options
+            .options
+            .push(ScanOption::new("--data-string", Some("it's")));
+        assert_eq!(
+            options.to_string(),
+            "nmap --script-args 'http.useragent=A B' --data-string 'it'\\''s' x"
+        );
+    }
+
+    #[test]
+    fn edited_options() {
+        let mut options = ScanOptions::parse("nmap -p80 --script=http-title -T4 192.0.2.1");
+        options.options[0].value = Some("443".to_string());
+        options.options[1].name = "--script-args".to_string();
+
+        assert_eq!(
+            options.to_string(),
+            "nmap -p 443 --script-args http-title -T4 192.0.2.1"
+        );
+    }
+}
@@ -0,0 +1,213 @@
+//!Scans loaded from many files.
+//!
+//!```
+//!use nmap_xml_parser::collection::ScanCollection;
+//!
+//!let scans = ScanCollection::from_glob("tests/*.xml").unwrap();
+//!for failure in scans.failures() {
+//!    eprintln!("{}: {}", failure.path.display(), failure.error);
+//!}
+//!for host in scans.hosts() {
+//!    println!("{:?} from {}", host.addresses().next(), host.source().unwrap().display());
+//!}
+//!```
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::host::Host;
+use crate::port::Port;
+use crate::vulns::Finding;
+use crate::{Error, NmapResults, ParseOptions, ParseWarning};
+
+///File name extensions of the files loaded by
+///[`ScanCollection::from_dir()`](struct.ScanCollection.html#method.from_dir).
+const EXTENSIONS: &[&str] = &[".xml", ".xml.gz", ".xml.zst", ".xml.xz"];
+
+///The results of scans loaded from many files.
+///
+///Each host records the file it was loaded from, see
+///[`Host::source()`](../host/struct.Host.html#method.source). Files that
+///fail to load are recorded as [`LoadFailure`](struct.LoadFailure.html)s
+///instead of failing the whole collection.
+#[derive(Debug, Default)]
+pub struct ScanCollection {
+    scans: Vec<Scan>,
+    failures: Vec<LoadFailure>,
+    options: ParseOptions,
+}
+
+///The results of one file in a [`ScanCollection`](struct.ScanCollection.html).
+#[derive(Clone, Debug)]
+pub struct Scan {
+    pub path: PathBuf,
+    pub results: NmapResults,
+}
+
+///A file that could not be loaded into a
+///[`ScanCollection`](struct.ScanCollection.html).
+#[derive(Debug)]
+pub struct LoadFailure {
+    pub path: PathBuf,
+    pub error: Error,
+}
+
+impl ScanCollection {
+    ///Creates an empty collection that parses files according to `options`.
+    pub fn with_options(options: &ParseOptions) -> Self {
+        ScanCollection {
+            options: options.clone(),
+            ..Default::default()
+        }
+    }
+
+    ///Loads every Nmap XML file in `dir`, including ones compressed with
+    ///gzip, zstd or xz, in order of their paths. Subdirectories are not
+    ///searched.
+    ///
+    ///An error is only returned if `dir` cannot be read.
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> Result<Self, Error> {
+        Self::from_dir_with_options(dir, &ParseOptions::default())
+    }
+
+    ///Loads every Nmap XML file in `dir` according to `options`. See
+    ///[`from_dir()`](#method.from_dir).
+    pub fn from_dir_with_options<P: AsRef<Path>>(
+        dir: P,
+        options: &ParseOptions,
+    ) -> Result<Self, Error> {
+        let mut collection = Self::with_options(options);
+        collection.add_dir(dir)?;
+        Ok(collection)
+    }
+
+    ///Loads every file matching the glob `pattern`, in order of their
+    ///paths.
+    ///
+    ///An error is only returned if `pattern` is invalid.
+    pub fn from_glob(pattern: &str) -> Result<Self, Error> {
+        Self::from_glob_with_options(pattern, &ParseOptions::default())
+    }
+
+    ///Loads every file matching the glob `pattern` according to `options`.
+    ///See [`from_glob()`](#method.from_glob).
+    pub fn from_glob_with_options(pattern: &str, options: &ParseOptions) -> Result<Self, Error> {
+        let mut collection = Self::with_options(options);
+        collection.add_glob(pattern)?;
+        Ok(collection)
+    }
+
+    ///Loads every Nmap XML file in `dir` into the collection. See
+    ///[`from_dir()`](#method.from_dir).
+    pub fn add_dir<P: AsRef<Path>>(&mut self, dir: P) -> Result<(), Error> {
+        let mut paths = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            if path.is_file() && EXTENSIONS.iter().any(|e| name.ends_with(e)) {
+                paths.push(path);
+            }
+        }
+
+        paths.sort();
+        for path in paths {
+            self.add_path(path);
+        }
+        Ok(())
+    }
+
+    ///Loads every file matching the glob `pattern` into the collection. See
+    ///[`from_glob()`](#method.from_glob).
+    pub fn add_glob(&mut self, pattern: &str) -> Result<(), Error> {
+        for entry in glob::glob(pattern)? {
+            match entry {
+                Ok(path) if path.is_dir() => {}
+                Ok(path) => {
+                    self.add_path(path);
+                }
+                Err(e) => self.failures.push(LoadFailure {
+                    path: e.path().to_path_buf(),
+                    error: std::io::Error::from(e).into(),
+                }),
+            }
+        }
+        Ok(())
+    }
+
+    ///Loads the file at `path` into the collection. Returns false and
+    ///records a [`LoadFailure`](struct.LoadFailure.html) if it could not be
+    ///loaded.
+    pub fn add_path<P: AsRef<Path>>(&mut self, path: P) -> bool {
+        let path = path.as_ref().to_path_buf();
+        let results = fs::File::open(&path)
+            .map_err(Error::from)
+            .and_then(|f| NmapResults::from_reader_with_options(f, &self.options));
+
+        match results {
+            Ok(results) => {
+                self.add(path, results);
+                true
+            }
+            Err(error) => {
+                self.failures.push(LoadFailure { path, error });
+                false
+            }
+        }
+    }
+
+    ///Adds results that were already parsed, recording `path` as the source
+    ///of their hosts.
+    pub fn add<P: AsRef<Path>>(&mut self, path: P, mut results: NmapResults) {
+        let path = path.as_ref().to_path_buf();
+        for host in &mut results.hosts {
+            host.source = Some(path.clone());
+        }
+        self.scans.push(Scan { path, results });
+    }
+
+    ///Returns an iterator over the files that were loaded.
+    pub fn scans(&self) -> std::slice::Iter<'_, Scan> {
+        self.scans.iter()
+    }
+
+    ///Returns an iterator over the files that could not be loaded.
+    pub fn failures(&self) -> std::slice::Iter<'_, LoadFailure> {
+        self.failures.iter()
+    }
+
+    ///Returns an iterator over the hosts in all scans.
+    pub fn hosts(&self) -> std::vec::IntoIter<&Host> {
+        self.scans
+            .iter()
+            .flat_map(|s| s.results.hosts())
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    ///Returns an iterator over the ports in all scans.
+    pub fn iter_ports(&self) -> std::vec::IntoIter<(&Host, &Port)> {
+        self.scans
+            .iter()
+            .flat_map(|s| s.results.iter_ports())
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    ///Returns an iterator over the vulnerability findings in all scans.
+    pub fn findings(&self) -> std::vec::IntoIter<Finding<'_>> {
+        self.scans
+            .iter()
+            .flat_map(|s| s.results.findings())
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    ///Returns an iterator over the problems recovered from while parsing
+    ///each file, with the path of the file.
+    pub fn warnings(&self) -> std::vec::IntoIter<(&Path, &ParseWarning)> {
+        self.scans
+            .iter()
+            .flat_map(|s| s.results.warnings().map(move |w| (s.path.as_path(), w)))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
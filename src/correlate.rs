@@ -0,0 +1,261 @@
+//!Grouping of host records that belong to the same machine.
+//!
+//!Nmap reports the IPv4 and IPv6 addresses of a dual-stack machine as
+//!separate hosts, usually in separate scans. [`correlate()`](fn.correlate.html)
+//!groups them back together.
+//!
+//!```
+//!# use std::path::PathBuf;
+//!use nmap_xml_parser::NmapResults;
+//!use nmap_xml_parser::correlate::correlate;
+//!# let path = |name: &str| {
+//!#     let mut p = PathBuf::new();
+//!#     p.push(&std::env::var("CARGO_MANIFEST_DIR").unwrap());
+//!#     p.push(name);
+//!#     p
+//!# };
+//!let v4 = NmapResults::from_path(path("tests/ipv4-dual.xml")).unwrap();
+//!let v6 = NmapResults::from_path(path("tests/ipv6.xml")).unwrap();
+//!
+//!for machine in correlate(v4.hosts().chain(v6.hosts())) {
+//!    if machine.is_dual_stack() {
+//!        println!("{:?} {:?}", machine.ipv4(), machine.ipv6());
+//!    }
+//!}
+//!```
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crate::host::{names_eq, Address, Host};
+
+///Host records that belong to the same machine.
+#[derive(Clone, Debug)]
+pub struct LogicalHost<'a> {
+    hosts: Vec<&'a Host>,
+}
+
+impl<'a> LogicalHost<'a> {
+    ///Returns an iterator over the host records, in the order they were
+    ///given to [`correlate()`](fn.correlate.html).
+    pub fn hosts(&self) -> std::slice::Iter<'_, &'a Host> {
+        self.hosts.iter()
+    }
+
+    ///Returns the IPv4 addresses of the machine, without duplicates.
+    pub fn ipv4(&self) -> Vec<Ipv4Addr> {
+        let mut r = Vec::new();
+        for ip in self.ip_addresses() {
+            if let IpAddr::V4(ip) = ip {
+                if !r.contains(&ip) {
+                    r.push(ip);
+                }
+            }
+        }
+        r
+    }
+
+    ///Returns the IPv6 addresses of the machine, including link-local
+    ///ones, without duplicates.
+    pub fn ipv6(&self) -> Vec<Ipv6Addr> {
+        let mut r = Vec::new();
+        for ip in self.ip_addresses() {
+            if let IpAddr::V6(ip) = ip {
+                if !r.contains(&ip) {
+                    r.push(ip);
+                }
+            }
+        }
+        r
+    }
+
+    ///Returns the MAC addresses of the machine, without duplicates. MAC
+    ///addresses are compared case-insensitively.
+    pub fn mac_addresses(&self) -> Vec<&'a str> {
+        let mut r: Vec<&str> = Vec::new();
+        for host in &self.hosts {
+            for address in host.addresses() {
+                if let Address::MacAddr(mac) = address {
+                    if !r.iter().any(|m| m.eq_ignore_ascii_case(mac)) {
+                        r.push(mac);
+                    }
+                }
+            }
+        }
+        r
+    }
+
+    ///Returns the names of the machine, with duplicates removed as by
+    ///[`Host::unique_names()`](../host/struct.Host.html#method.unique_names).
+    pub fn names(&self) -> Vec<&'a str> {
+        let mut r: Vec<&str> = Vec::new();
+        for host in &self.hosts {
+            for name in host.unique_names() {
+                if !r.iter().any(|n| names_eq(n, name)) {
+                    r.push(name);
+                }
+            }
+        }
+        r
+    }
+
+    ///Returns true if the machine has both an IPv4 and an IPv6 address.
+    ///Link-local IPv6 addresses count.
+    pub fn is_dual_stack(&self) -> bool {
+        let mut ips = self.ip_addresses();
+        let v4 = ips.clone().any(|ip| ip.is_ipv4());
+        v4 && ips.any(|ip| ip.is_ipv6())
+    }
+
+    fn ip_addresses(&self) -> impl Iterator<Item = IpAddr> + Clone + '_ {
+        self.hosts
+            .iter()
+            .flat_map(|h| h.addresses())
+            .filter_map(Address::ip)
+    }
+}
+
+///Groups `hosts` into the machines they belong to.
+///
+///Hosts are grouped if they share a MAC address, a name, or an IP address
+///other than an IPv6 link-local one, which is only unique on its link.
+///Grouping is transitive, so a host that shares its MAC address with one
+///host and its name with another joins all three. MAC addresses are
+///compared case-insensitively, and names as by
+///[`Host::unique_names()`](../host/struct.Host.html#method.unique_names).
+///
+///Machines are returned in order of their first host in `hosts`.
+pub fn correlate<'a, I>(hosts: I) -> Vec<LogicalHost<'a>>
+where
+    I: IntoIterator<Item = &'a Host>,
+{
+    let hosts = hosts.into_iter().collect::<Vec<_>>();
+    let mut parent = (0..hosts.len()).collect::<Vec<_>>();
+    let mut owners: HashMap<String, usize> = HashMap::new();
+
+    for (i, host) in hosts.iter().enumerate() {
+        for key in keys(host) {
+            match owners.get(&key) {
+                Some(&j) => union(&mut parent, i, j),
+                None => {
+                    owners.insert(key, i);
+                }
+            }
+        }
+    }
+
+    let mut groups: Vec<LogicalHost> = Vec::new();
+    let mut group_of: HashMap<usize, usize> = HashMap::new();
+    for (i, host) in hosts.into_iter().enumerate() {
+        let root = find(&mut parent, i);
+        let g = *group_of.entry(root).or_insert_with(|| {
+            groups.push(LogicalHost { hosts: Vec::new() });
+            groups.len() - 1
+        });
+        groups[g].hosts.push(host);
+    }
+    groups
+}
+
+///Returns the values identifying `host`, each prefixed with its kind so
+///that e.g. a name cannot match an address.
+fn keys(host: &Host) -> Vec<String> {
+    let mut r = Vec::new();
+    for address in host.addresses() {
+        match address {
+            Address::MacAddr(mac) => r.push(format!("mac {}", mac.to_ascii_lowercase())),
+            Address::IpAddr(IpAddr::V6(ip)) if is_link_local(ip) => {}
+            Address::IpAddr(ip) => r.push(format!("ip {}", ip)),
+        }
+    }
+    for name in host.unique_names() {
+        r.push(format!(
+            "name {}",
+            name.trim_end_matches('.').to_ascii_lowercase()
+        ));
+    }
+    r
+}
+
+///Returns true if `ip` is in `fe80::/10`.
+fn is_link_local(ip: &Ipv6Addr) -> bool {
+    ip.segments()[0] & 0xffc0 == 0xfe80
+}
+
+fn find(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+fn union(parent: &mut [usize], a: usize, b: usize) {
+    let (a, b) = (find(parent, a), find(parent, b));
+    //The earlier host becomes the root, so that groups keep input order.
+    if a < b {
+        parent[b] = a;
+    } else {
+        parent[a] = b;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::NmapResults;
+
+    fn host(addresses: &[(&str, &str)], name: Option<&str>) -> String {
+        let mut r = r#"<host><status state="up" reason="user-set" reason_ttl="0"/>"#.to_string();
+        for (addr, addrtype) in addresses {
+            r.push_str(&format!(
+                r#"<address addr="{}" addrtype="{}"/>"#,
+                addr, addrtype
+            ));
+        }
+        if let Some(name) = name {
+            r.push_str(&format!(
+                r#"<hostnames><hostname name="{}" type="PTR"/></hostnames>"#,
+                name
+            ));
+        }
+        r.push_str("</host>");
+        r
+    }
+
+    #[test]
+    fn groups_transitively() {
+        let xml = format!(
+            r#"<nmaprun start="1">{}{}{}{}{}</nmaprun>"#,
+            host(&[("192.0.2.1", "ipv4"), ("00:00:5E:00:53:01", "mac")], None),
+            host(
+                &[("fe80::1", "ipv6"), ("00:00:5e:00:53:01", "mac")],
+                Some("a.example")
+            ),
+            host(&[("2001:db8::1", "ipv6")], Some("A.example.")),
+            host(&[("fe80::1%eth1", "ipv6")], None),
+            host(&[("192.0.2.1", "ipv4")], None),
+        );
+        let results = NmapResults::parse(&xml).unwrap();
+        let machines = correlate(results.hosts());
+
+        assert_eq!(machines.len(), 2);
+        let a = &machines[0];
+        assert_eq!(a.hosts().len(), 4);
+        assert_eq!(a.ipv4(), vec!["192.0.2.1".parse::<Ipv4Addr>().unwrap()]);
+        assert_eq!(
+            a.ipv6(),
+            vec![
+                "fe80::1".parse::<Ipv6Addr>().unwrap(),
+                "2001:db8::1".parse().unwrap()
+            ]
+        );
+        assert_eq!(a.mac_addresses(), vec!["00:00:5E:00:53:01"]);
+        assert_eq!(a.names(), vec!["a.example"]);
+        assert!(a.is_dual_stack());
+
+        //The link-local address alone does not identify the host.
+        let b = &machines[1];
+        assert_eq!(b.ipv6(), vec!["fe80::1".parse::<Ipv6Addr>().unwrap()]);
+        assert!(!b.is_dual_stack());
+    }
+}
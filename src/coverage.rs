@@ -0,0 +1,279 @@
+//!Which ports of a host were scanned, and in which state.
+//!
+//!Nmap lists ports individually only if they are not in the most common
+//!states of a host. The other ports are summarized in `extraports`, so
+//![`PortInfo::ports()`](../port/struct.PortInfo.html#method.ports) alone
+//!does not tell a closed port from one that was never scanned.
+//!
+//!```
+//!# use std::path::PathBuf;
+//!# use std::fs;
+//!use nmap_xml_parser::NmapResults;
+//!use nmap_xml_parser::port::PortState;
+//!# let mut nmap_xml_file = PathBuf::new();
+//!# nmap_xml_file.push(&std::env::var("CARGO_MANIFEST_DIR").unwrap());
+//!# nmap_xml_file.push("tests/scanme-version.xml");
+//!let content = fs::read_to_string(nmap_xml_file).unwrap();
+//!let results = NmapResults::parse(&content).unwrap();
+//!
+//!for (host, coverage) in results.coverage() {
+//!    for c in coverage {
+//!        println!(
+//!            "{:?}: {} {} ports scanned, {} closed",
+//!            host.primary_name(),
+//!            c.scanned.len(),
+//!            c.protocol,
+//!            c.total(&PortState::Closed)
+//!        );
+//!        assert_eq!(c.state(443), Some(&PortState::Closed));
+//!        assert_eq!(c.state(8), None);
+//!    }
+//!}
+//!```
+use std::collections::HashMap;
+
+use crate::host::Host;
+use crate::port::{ExtraPorts, PortProtocol, PortState};
+use crate::portset::PortSet;
+use crate::ScanInfo;
+
+///The ports of one protocol that were scanned on a host.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Coverage {
+    pub protocol: PortProtocol,
+    ///Ports that were probed, from `scaninfo`.
+    pub scanned: PortSet,
+    ///Ports that were listed individually, by state.
+    pub listed: HashMap<PortState, PortSet>,
+    ///Ports that were not listed individually, by the state `extraports`
+    ///gives for them.
+    pub implicit: HashMap<PortState, PortSet>,
+    ///Scanned ports that were not listed individually and whose state is
+    ///not known, e.g. because `extraports` covers several states without
+    ///naming the ports in each.
+    pub unknown: PortSet,
+    ///Number of ports in each state, whether or not they were listed
+    ///individually.
+    pub totals: HashMap<PortState, usize>,
+}
+
+impl Coverage {
+    ///Returns the state of `port`, or `None` if it was not scanned or its
+    ///state is not known.
+    pub fn state(&self, port: u16) -> Option<&PortState> {
+        self.listed
+            .iter()
+            .chain(self.implicit.iter())
+            .find(|(_, ports)| ports.contains(port))
+            .map(|(state, _)| state)
+    }
+
+    ///Returns the number of ports in `state`.
+    pub fn total(&self, state: &PortState) -> usize {
+        self.totals.get(state).copied().unwrap_or(0)
+    }
+
+    ///Returns the ports known to be in `state`, listed or not.
+    pub fn ports(&self, state: &PortState) -> PortSet {
+        let empty = PortSet::new();
+        let listed = self.listed.get(state).unwrap_or(&empty);
+        let implicit = self.implicit.get(state).unwrap_or(&empty);
+        listed.union(implicit)
+    }
+}
+
+///Returns the coverage of `host` for each protocol in `scan_info`.
+pub(crate) fn host_coverage(host: &Host, scan_info: &[&ScanInfo]) -> Vec<Coverage> {
+    let mut protocols = Vec::new();
+    for info in scan_info {
+        if !protocols.contains(&info.protocol) {
+            protocols.push(info.protocol.clone());
+        }
+    }
+
+    let single_protocol = protocols.len() == 1;
+    protocols
+        .into_iter()
+        .map(|protocol| {
+            let scanned = scan_info
+                .iter()
+                .filter(|i| i.protocol == protocol)
+                .fold(PortSet::new(), |r, i| r.union(&i.services));
+            protocol_coverage(host, protocol, scanned, single_protocol)
+        })
+        .collect()
+}
+
+fn protocol_coverage(
+    host: &Host,
+    protocol: PortProtocol,
+    scanned: PortSet,
+    single_protocol: bool,
+) -> Coverage {
+    let mut listed: HashMap<PortState, PortSet> = HashMap::new();
+    let mut totals: HashMap<PortState, usize> = HashMap::new();
+    for port in host.port_info.ports().filter(|p| p.protocol == protocol) {
+        listed
+            .entry(port.status.state.clone())
+            .or_default()
+            .insert(port.port_number);
+    }
+    for (state, ports) in &listed {
+        *totals.entry(state.clone()).or_default() += ports.len();
+    }
+
+    let unlisted = listed
+        .values()
+        .fold(scanned.clone(), |r, ports| r.difference(ports));
+
+    //Ports named by extrareasons are attributed directly. Other ports are
+    //only attributed if a single extraports state is left for them.
+    let mut implicit: HashMap<PortState, PortSet> = HashMap::new();
+    let mut unnamed = Vec::new();
+    for extra in host.port_info.extra_ports() {
+        if !applies_to(extra, &protocol, single_protocol) {
+            continue;
+        }
+        *totals.entry(extra.state.clone()).or_default() += extra.count as usize;
+
+        let mut named = 0;
+        for reason in &extra.reasons {
+            if let Some(ports) = &reason.ports {
+                if reason.protocol.as_ref().map_or(true, |p| *p == protocol) {
+                    let ports = ports.intersection(&unlisted);
+                    named += ports.len();
+                    let set = implicit.entry(extra.state.clone()).or_default();
+                    *set = set.union(&ports);
+                }
+            }
+        }
+        if named < extra.count as usize {
+            unnamed.push((extra.state.clone(), extra.count as usize - named));
+        }
+    }
+
+    let unknown = implicit
+        .values()
+        .fold(unlisted, |r, ports| r.difference(ports));
+    let unknown = match unnamed.as_slice() {
+        [(state, count)] if *count == unknown.len() => {
+            let set = implicit.entry(state.clone()).or_default();
+            *set = set.union(&unknown);
+            PortSet::new()
+        }
+        _ => unknown,
+    };
+
+    Coverage {
+        protocol,
+        scanned,
+        listed,
+        implicit,
+        unknown,
+        totals,
+    }
+}
+
+///Returns true if `extra` describes ports of `protocol`. Before Nmap 7.90,
+///`extraports` does not say which protocol it describes, so it is only
+///used if a single protocol was scanned.
+fn applies_to(extra: &ExtraPorts, protocol: &PortProtocol, single_protocol: bool) -> bool {
+    let mut protocols = extra.reasons.iter().filter_map(|r| r.protocol.as_ref());
+    match protocols.next() {
+        Some(p) => p == protocol || protocols.any(|p| p == protocol),
+        None => single_protocol,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::NmapResults;
+
+    fn coverage(scaninfo: &str, ports: &str) -> Vec<Coverage> {
+        let xml = format!(
+            r#"<nmaprun start="1588318812">{}
+<host><status state="up" reason="user-set" reason_ttl="0"/>
+<address addr="192.0.2.1" addrtype="ipv4"/>
+<ports>{}</ports>
+</host>
+</nmaprun>"#,
+            scaninfo, ports
+        );
+        let results = NmapResults::parse(&xml).unwrap();
+        results.coverage().next().unwrap().1
+    }
+
+    fn port(protocol: &str, number: u16, state: &str) -> String {
+        format!(
+            r#"<port protocol="{}" portid="{}"><state state="{}" reason="x" reason_ttl="64"/></port>"#,
+            protocol, number, state
+        )
+    }
+
+    #[test]
+    fn single_extraports() {
+        let c = coverage(
+            r#"<scaninfo type="syn" protocol="tcp" numservices="10" services="1-10"/>"#,
+            &(r#"<extraports state="closed" count="8"><extrareasons reason="resets" count="8"/></extraports>"#.to_string()
+                + &port("tcp", 2, "open")
+                + &port("tcp", 3, "filtered")),
+        );
+
+        assert_eq!(c.len(), 1);
+        let c = &c[0];
+        assert_eq!(c.scanned.len(), 10);
+        assert_eq!(c.state(2), Some(&PortState::Open));
+        assert_eq!(c.state(3), Some(&PortState::Filtered));
+        assert_eq!(c.state(10), Some(&PortState::Closed));
+        assert_eq!(c.state(11), None);
+        assert_eq!(c.ports(&PortState::Closed).to_string(), "1,4-10");
+        assert_eq!(c.total(&PortState::Closed), 8);
+        assert_eq!(c.total(&PortState::Open), 1);
+        assert!(c.unknown.is_empty());
+    }
+
+    #[test]
+    fn several_extraports() {
+        let c = coverage(
+            r#"<scaninfo type="syn" protocol="tcp" numservices="10" services="1-10"/>"#,
+            &(r#"<extraports state="closed" count="5"><extrareasons reason="resets" count="5"/></extraports>
+<extraports state="filtered" count="4"><extrareasons reason="no-responses" count="4"/></extraports>"#
+                .to_string()
+                + &port("tcp", 2, "open")),
+        );
+
+        let c = &c[0];
+        assert_eq!(c.total(&PortState::Closed), 5);
+        assert_eq!(c.total(&PortState::Filtered), 4);
+        assert_eq!(c.state(1), None);
+        assert_eq!(c.unknown.to_string(), "1,3-10");
+    }
+
+    #[test]
+    fn named_extraports() {
+        let c = coverage(
+            r#"<scaninfo type="syn" protocol="tcp" numservices="10" services="1-10"/>
+<scaninfo type="udp" protocol="udp" numservices="3" services="53,123,161"/>"#,
+            &(r#"<extraports state="closed" count="5"><extrareasons reason="reset" count="5" proto="tcp" ports="1,4-7"/></extraports>
+<extraports state="filtered" count="4"><extrareasons reason="no-response" count="4" proto="tcp" ports="3,8-10"/></extraports>
+<extraports state="open|filtered" count="2"><extrareasons reason="no-response" count="2" proto="udp" ports="123,161"/></extraports>"#
+                .to_string()
+                + &port("tcp", 2, "open")
+                + &port("udp", 53, "open")),
+        );
+
+        assert_eq!(c.len(), 2);
+        let (tcp, udp) = (&c[0], &c[1]);
+        assert_eq!(tcp.protocol, PortProtocol::Tcp);
+        assert_eq!(tcp.ports(&PortState::Closed).to_string(), "1,4-7");
+        assert_eq!(tcp.ports(&PortState::Filtered).to_string(), "3,8-10");
+        assert!(tcp.unknown.is_empty());
+        assert_eq!(tcp.total(&PortState::OpenFiltered), 0);
+
+        assert_eq!(udp.protocol, PortProtocol::Udp);
+        assert_eq!(udp.state(53), Some(&PortState::Open));
+        assert_eq!(udp.ports(&PortState::OpenFiltered).to_string(), "123,161");
+        assert_eq!(udp.total(&PortState::Closed), 0);
+    }
+}
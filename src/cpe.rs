@@ -0,0 +1,693 @@
+//!Common Platform Enumeration (CPE) names.
+//!
+//!Nmap reports CPE names in the CPE 2.2 URI binding, for example
+//!`cpe:/a:openbsd:openssh:7.4`. [`Cpe`](struct.Cpe.html) parses both that
+//!form and the CPE 2.3 formatted string binding used by the NVD, so scan
+//!results can be matched against vulnerability data.
+use roxmltree::Node;
+use std::fmt;
+use std::str::FromStr;
+use strum_macros::{Display, EnumString};
+
+use crate::Error;
+
+///The `part` component of a CPE name.
+#[derive(EnumString, Display, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CpePart {
+    #[strum(serialize = "a")]
+    Application,
+    #[strum(serialize = "h")]
+    Hardware,
+    #[strum(serialize = "o")]
+    OperatingSystem,
+}
+
+///A single component of a CPE name.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CpeValue {
+    ///The component may take any value. Written as an empty component in
+    ///CPE 2.2 and as `*` in CPE 2.3.
+    #[default]
+    Any,
+    ///The component is not applicable. Written as `-`.
+    NotApplicable,
+    ///A concrete value.
+    Value(String),
+    ///A value with wildcards, for use as a pattern in
+    ///[`Cpe::matches()`](struct.Cpe.html#method.matches). `*` matches any
+    ///sequence of characters and `?` matches a single character, unless
+    ///quoted with `\`, which also quotes itself.
+    Pattern(String),
+}
+
+impl CpeValue {
+    ///Returns the concrete value, if any.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            CpeValue::Value(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn matches(&self, other: &CpeValue) -> bool {
+        match (self, other) {
+            (CpeValue::Any, _) => true,
+            (CpeValue::NotApplicable, CpeValue::NotApplicable) => true,
+            (CpeValue::Value(p), CpeValue::Value(v)) => p.eq_ignore_ascii_case(v),
+            (CpeValue::Pattern(p), CpeValue::Value(v)) => {
+                wildcard_match(&tokens(p, true), &v.chars().collect::<Vec<_>>())
+            }
+            _ => false,
+        }
+    }
+
+    ///Returns the characters and wildcards of the value.
+    fn tokens(&self) -> Vec<Token> {
+        match self {
+            CpeValue::Value(s) => tokens(s, false),
+            CpeValue::Pattern(s) => tokens(s, true),
+            CpeValue::Any | CpeValue::NotApplicable => Vec::new(),
+        }
+    }
+
+    ///Builds a value from decoded tokens, which is a pattern if any of them
+    ///is a wildcard.
+    fn from_tokens(tokens: &[Token]) -> Self {
+        if !tokens
+            .iter()
+            .any(|t| matches!(t, Token::AnyChars | Token::OneChar))
+        {
+            let value = tokens
+                .iter()
+                .filter_map(|t| match t {
+                    Token::Char(c) => Some(*c),
+                    _ => None,
+                })
+                .collect();
+            return CpeValue::Value(value);
+        }
+
+        let mut pattern = String::new();
+        for t in tokens {
+            match t {
+                Token::AnyChars => pattern.push('*'),
+                Token::OneChar => pattern.push('?'),
+                Token::Char(c) => {
+                    if matches!(c, '*' | '?' | '\\') {
+                        pattern.push('\\');
+                    }
+                    pattern.push(*c);
+                }
+            }
+        }
+        CpeValue::Pattern(pattern)
+    }
+}
+
+///A character of a component value, or a wildcard.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Token {
+    Char(char),
+    ///`*`, any sequence of characters.
+    AnyChars,
+    ///`?`, any single character.
+    OneChar,
+}
+
+///Splits `s` into tokens. In a pattern, unquoted `*` and `?` are wildcards
+///and `\` quotes the next character.
+fn tokens(s: &str, pattern: bool) -> Vec<Token> {
+    if !pattern {
+        return s.chars().map(Token::Char).collect();
+    }
+
+    let mut r = Vec::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        r.push(match c {
+            '*' => Token::AnyChars,
+            '?' => Token::OneChar,
+            '\\' => Token::Char(chars.next().unwrap_or('\\')),
+            c => Token::Char(c),
+        });
+    }
+    r
+}
+
+///A CPE name such as `cpe:/a:openbsd:openssh:7.4`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Cpe {
+    pub part: CpePart,
+    pub vendor: CpeValue,
+    pub product: CpeValue,
+    pub version: CpeValue,
+    pub update: CpeValue,
+    pub edition: CpeValue,
+    pub language: CpeValue,
+    pub sw_edition: CpeValue,
+    pub target_sw: CpeValue,
+    pub target_hw: CpeValue,
+    pub other: CpeValue,
+}
+
+impl Cpe {
+    ///Creates a CPE name with the given part and every other component set
+    ///to [`CpeValue::Any`](enum.CpeValue.html#variant.Any).
+    pub fn new(part: CpePart) -> Self {
+        Cpe {
+            part,
+            vendor: CpeValue::Any,
+            product: CpeValue::Any,
+            version: CpeValue::Any,
+            update: CpeValue::Any,
+            edition: CpeValue::Any,
+            language: CpeValue::Any,
+            sw_edition: CpeValue::Any,
+            target_sw: CpeValue::Any,
+            target_hw: CpeValue::Any,
+            other: CpeValue::Any,
+        }
+    }
+
+    ///Returns the name in the CPE 2.2 URI binding, e.g.
+    ///`cpe:/a:openbsd:openssh:7.4`. This is also the `Display` output.
+    pub fn to_uri(&self) -> String {
+        let extended = [
+            &self.sw_edition,
+            &self.target_sw,
+            &self.target_hw,
+            &self.other,
+        ];
+
+        let edition = if extended.iter().all(|v| **v == CpeValue::Any) {
+            encode_uri_value(&self.edition)
+        } else {
+            let mut packed = String::new();
+            for v in std::iter::once(&self.edition).chain(extended.iter().copied()) {
+                packed.push('~');
+                packed.push_str(&encode_uri_value(v));
+            }
+            packed
+        };
+
+        let mut components = vec![
+            self.part.to_string(),
+            encode_uri_value(&self.vendor),
+            encode_uri_value(&self.product),
+            encode_uri_value(&self.version),
+            encode_uri_value(&self.update),
+            edition,
+            encode_uri_value(&self.language),
+        ];
+        while components.last().is_some_and(|c| c.is_empty()) {
+            components.pop();
+        }
+
+        format!("cpe:/{}", components.join(":"))
+    }
+
+    ///Returns the name in the CPE 2.3 formatted string binding, e.g.
+    ///`cpe:2.3:a:openbsd:openssh:7.4:*:*:*:*:*:*:*`.
+    pub fn to_cpe23(&self) -> String {
+        let components = [
+            &self.vendor,
+            &self.product,
+            &self.version,
+            &self.update,
+            &self.edition,
+            &self.language,
+            &self.sw_edition,
+            &self.target_sw,
+            &self.target_hw,
+            &self.other,
+        ];
+
+        let mut s = format!("cpe:2.3:{}", self.part);
+        for c in components.iter() {
+            s.push(':');
+            s.push_str(&encode_fs_value(c));
+        }
+        s
+    }
+
+    ///Returns true if `other` is matched by this name used as a pattern.
+    ///
+    ///The parts must be equal. Components of this name that are
+    ///[`CpeValue::Any`](enum.CpeValue.html#variant.Any) match anything, while
+    ///`Any` in `other`, e.g. the missing version of `cpe:/a:openbsd:openssh`,
+    ///is only matched by `Any`. Concrete values are compared
+    ///case-insensitively, and
+    ///[`CpeValue::Pattern`](enum.CpeValue.html#variant.Pattern) components
+    ///of this name match with wildcards.
+    pub fn matches(&self, other: &Cpe) -> bool {
+        self.part == other.part
+            && self.vendor.matches(&other.vendor)
+            && self.product.matches(&other.product)
+            && self.version.matches(&other.version)
+            && self.update.matches(&other.update)
+            && self.edition.matches(&other.edition)
+            && self.language.matches(&other.language)
+            && self.sw_edition.matches(&other.sw_edition)
+            && self.target_sw.matches(&other.target_sw)
+            && self.target_hw.matches(&other.target_hw)
+            && self.other.matches(&other.other)
+    }
+
+    fn parse_uri(s: &str) -> Result<Self, Error> {
+        let components: Vec<&str> = s.split(':').collect();
+        if components.len() > 7 {
+            return Err(Error::InvalidCpe(s.to_string()));
+        }
+
+        let part =
+            CpePart::from_str(components[0]).map_err(|_| Error::InvalidCpe(s.to_string()))?;
+        let mut cpe = Cpe::new(part);
+
+        let get = |i: usize| -> Result<CpeValue, Error> {
+            components
+                .get(i)
+                .map_or(Ok(CpeValue::Any), |c| decode_uri_value(c))
+                .map_err(|_| Error::InvalidCpe(s.to_string()))
+        };
+
+        cpe.vendor = get(1)?;
+        cpe.product = get(2)?;
+        cpe.version = get(3)?;
+        cpe.update = get(4)?;
+        cpe.language = get(6)?;
+
+        match components.get(5) {
+            Some(e) if e.starts_with('~') => {
+                let packed: Vec<&str> = e[1..].split('~').collect();
+                if packed.len() != 5 {
+                    return Err(Error::InvalidCpe(s.to_string()));
+                }
+                let mut values = Vec::with_capacity(5);
+                for p in packed {
+                    values.push(decode_uri_value(p).map_err(|_| Error::InvalidCpe(s.to_string()))?);
+                }
+                cpe.other = values.pop().unwrap();
+                cpe.target_hw = values.pop().unwrap();
+                cpe.target_sw = values.pop().unwrap();
+                cpe.sw_edition = values.pop().unwrap();
+                cpe.edition = values.pop().unwrap();
+            }
+            _ => cpe.edition = get(5)?,
+        }
+
+        Ok(cpe)
+    }
+
+    fn parse_fs(s: &str) -> Result<Self, Error> {
+        let components = split_fs(s);
+        if components.len() != 11 {
+            return Err(Error::InvalidCpe(s.to_string()));
+        }
+
+        let part =
+            CpePart::from_str(&components[0]).map_err(|_| Error::InvalidCpe(s.to_string()))?;
+        let mut values = components[1..].iter().map(|c| decode_fs_value(c));
+
+        let mut next = || values.next().unwrap();
+        Ok(Cpe {
+            part,
+            vendor: next(),
+            product: next(),
+            version: next(),
+            update: next(),
+            edition: next(),
+            language: next(),
+            sw_edition: next(),
+            target_sw: next(),
+            target_hw: next(),
+            other: next(),
+        })
+    }
+}
+
+impl FromStr for Cpe {
+    type Err = Error;
+
+    ///Parses a CPE 2.2 URI (`cpe:/...`) or a CPE 2.3 formatted string
+    ///(`cpe:2.3:...`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let has_prefix = |prefix: &str| {
+            s.get(..prefix.len())
+                .is_some_and(|p| p.eq_ignore_ascii_case(prefix))
+        };
+
+        if has_prefix("cpe:2.3:") {
+            Cpe::parse_fs(&s[8..])
+        } else if has_prefix("cpe:/") {
+            Cpe::parse_uri(&s[5..])
+        } else {
+            Err(Error::InvalidCpe(s.to_string()))
+        }
+    }
+}
+
+impl fmt::Display for Cpe {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_uri())
+    }
+}
+
+///Parses the `cpe` children of `node`. Names that cannot be parsed are
+///returned verbatim in the second vector rather than failing the parse,
+///since NSE scripts and OS fingerprint entries do not always emit valid
+///CPE names.
+pub(crate) fn parse_cpe_nodes(node: Node) -> (Vec<Cpe>, Vec<String>) {
+    let mut cpe = Vec::new();
+    let mut unparsed = Vec::new();
+
+    for child in node.children() {
+        if child.tag_name().name() == "cpe" {
+            let text = child.text().unwrap_or("");
+            match text.parse() {
+                Ok(c) => cpe.push(c),
+                Err(_) => unparsed.push(text.to_string()),
+            }
+        }
+    }
+
+    (cpe, unparsed)
+}
+
+fn decode_uri_value(s: &str) -> Result<CpeValue, ()> {
+    match s {
+        "" => return Ok(CpeValue::Any),
+        "-" => return Ok(CpeValue::NotApplicable),
+        _ => {}
+    }
+
+    //Percent-encoded bytes are decoded before splitting into characters,
+    //with the wildcards `%01` and `%02` kept apart from literal bytes.
+    let bytes = s.as_bytes();
+    let mut runs: Vec<(Vec<u8>, Option<Token>)> = vec![(Vec::new(), None)];
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = s.get(i + 1..i + 3).ok_or(())?;
+            match u8::from_str_radix(hex, 16).map_err(|_| ())? {
+                0x01 => runs.push((Vec::new(), Some(Token::OneChar))),
+                0x02 => runs.push((Vec::new(), Some(Token::AnyChars))),
+                b => runs.last_mut().unwrap().0.push(b),
+            }
+            i += 3;
+        } else {
+            runs.last_mut().unwrap().0.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    let mut tokens = Vec::new();
+    for (literal, wildcard) in runs {
+        tokens.extend(wildcard);
+        let literal = String::from_utf8(literal).map_err(|_| ())?;
+        tokens.extend(literal.chars().map(Token::Char));
+    }
+    Ok(CpeValue::from_tokens(&tokens))
+}
+
+fn encode_uri_value(v: &CpeValue) -> String {
+    match v {
+        CpeValue::Any => String::new(),
+        CpeValue::NotApplicable => "-".to_string(),
+        CpeValue::Value(_) | CpeValue::Pattern(_) => {
+            let mut out = String::new();
+            for t in v.tokens() {
+                match t {
+                    Token::OneChar => out.push_str("%01"),
+                    Token::AnyChars => out.push_str("%02"),
+                    Token::Char(c @ ('a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '_' | '-')) => {
+                        out.push(c)
+                    }
+                    Token::Char(c) => {
+                        let mut buf = [0; 4];
+                        for b in c.encode_utf8(&mut buf).bytes() {
+                            out.push_str(&format!("%{:02x}", b));
+                        }
+                    }
+                }
+            }
+            out
+        }
+    }
+}
+
+fn split_fs(s: &str) -> Vec<String> {
+    let mut components = vec![String::new()];
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                let last = components.last_mut().unwrap();
+                last.push(c);
+                if let Some(n) = chars.next() {
+                    last.push(n);
+                }
+            }
+            ':' => components.push(String::new()),
+            c => components.last_mut().unwrap().push(c),
+        }
+    }
+    components
+}
+
+fn decode_fs_value(s: &str) -> CpeValue {
+    match s {
+        "*" => return CpeValue::Any,
+        "-" => return CpeValue::NotApplicable,
+        _ => {}
+    }
+
+    //Escaped characters, including `\*` and `\?`, are literal.
+    let mut tokens = Vec::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        tokens.push(match c {
+            '\\' => match chars.next() {
+                Some(n) => Token::Char(n),
+                None => continue,
+            },
+            '*' => Token::AnyChars,
+            '?' => Token::OneChar,
+            c => Token::Char(c),
+        });
+    }
+    CpeValue::from_tokens(&tokens)
+}
+
+fn encode_fs_value(v: &CpeValue) -> String {
+    match v {
+        CpeValue::Any => "*".to_string(),
+        CpeValue::NotApplicable => "-".to_string(),
+        CpeValue::Value(_) | CpeValue::Pattern(_) => {
+            let mut out = String::new();
+            for t in v.tokens() {
+                match t {
+                    Token::OneChar => out.push('?'),
+                    Token::AnyChars => out.push('*'),
+                    Token::Char(c @ ('a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '_' | '-')) => {
+                        out.push(c)
+                    }
+                    Token::Char(c) => {
+                        out.push('\\');
+                        out.push(c);
+                    }
+                }
+            }
+            out
+        }
+    }
+}
+
+///Returns true if `value` is matched by `pattern`, ignoring ASCII case.
+///Backtracks only to the last `*`, so it runs in `O(pattern * value)` time.
+fn wildcard_match(pattern: &[Token], value: &[char]) -> bool {
+    let (mut p, mut v) = (0, 0);
+    //Position after the last `*` and the value position it resumes at.
+    let mut star = None;
+
+    while v < value.len() {
+        match pattern.get(p) {
+            Some(Token::AnyChars) => {
+                star = Some((p + 1, v));
+                p += 1;
+            }
+            Some(Token::OneChar) => {
+                p += 1;
+                v += 1;
+            }
+            Some(Token::Char(c)) if c.eq_ignore_ascii_case(&value[v]) => {
+                p += 1;
+                v += 1;
+            }
+            _ => match star {
+                Some((star_p, star_v)) => {
+                    p = star_p;
+                    v = star_v + 1;
+                    star = Some((star_p, star_v + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|t| *t == Token::AnyChars)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_uri() {
+        let cpe: Cpe = "cpe:/a:openbsd:openssh:7.4".parse().unwrap();
+
+        assert_eq!(cpe.part, CpePart::Application);
+        assert_eq!(cpe.vendor.as_str(), Some("openbsd"));
+        assert_eq!(cpe.product.as_str(), Some("openssh"));
+        assert_eq!(cpe.version.as_str(), Some("7.4"));
+        assert_eq!(cpe.update, CpeValue::Any);
+        assert_eq!(cpe.to_string(), "cpe:/a:openbsd:openssh:7.4");
+    }
+
+    #[test]
+    fn parse_uri_percent_encoded() {
+        let cpe: Cpe = "cpe:/a:hp:insight_diagnostics:7.4.0.1570:-:~~online~win2003~x64~"
+            .parse()
+            .unwrap();
+
+        assert_eq!(cpe.update, CpeValue::NotApplicable);
+        assert_eq!(cpe.edition, CpeValue::Any);
+        assert_eq!(cpe.sw_edition.as_str(), Some("online"));
+        assert_eq!(cpe.target_sw.as_str(), Some("win2003"));
+        assert_eq!(cpe.target_hw.as_str(), Some("x64"));
+        assert_eq!(
+            cpe.to_cpe23(),
+            "cpe:2.3:a:hp:insight_diagnostics:7.4.0.1570:-:*:*:online:win2003:x64:*"
+        );
+        assert_eq!(
+            cpe.to_uri(),
+            "cpe:/a:hp:insight_diagnostics:7.4.0.1570:-:~~online~win2003~x64~"
+        );
+
+        let cpe: Cpe = "cpe:/a:foo%7ebar:big%24money".parse().unwrap();
+        assert_eq!(cpe.vendor.as_str(), Some("foo~bar"));
+        assert_eq!(cpe.product.as_str(), Some("big$money"));
+        assert_eq!(
+            cpe.to_cpe23(),
+            "cpe:2.3:a:foo\\~bar:big\\$money:*:*:*:*:*:*:*:*"
+        );
+    }
+
+    #[test]
+    fn parse_cpe23() {
+        let cpe: Cpe = "cpe:2.3:o:microsoft:windows_10:1809:*:*:*:*:*:x64:*"
+            .parse()
+            .unwrap();
+
+        assert_eq!(cpe.part, CpePart::OperatingSystem);
+        assert_eq!(cpe.version.as_str(), Some("1809"));
+        assert_eq!(cpe.target_hw.as_str(), Some("x64"));
+
+        let cpe: Cpe = "cpe:2.3:a:foo\\:bar:baz:*:*:*:*:*:*:*:*".parse().unwrap();
+        assert_eq!(cpe.vendor.as_str(), Some("foo:bar"));
+    }
+
+    #[test]
+    fn invalid_cpe() {
+        assert!("cpe:/x:foo".parse::<Cpe>().is_err());
+        assert!("cpe:/a:foo:bar:1:2:3:4:5".parse::<Cpe>().is_err());
+        assert!("cpe:/a:foo%zz".parse::<Cpe>().is_err());
+        assert!("cpe:2.3:a:foo".parse::<Cpe>().is_err());
+        assert!("openssh".parse::<Cpe>().is_err());
+    }
+
+    #[test]
+    fn unparsed_cpe_is_kept() {
+        let xml = r#"<nmaprun start="1588318812">
+<host><status state="up" reason="user-set" reason_ttl="0"/>
+<address addr="192.0.2.1" addrtype="ipv4"/>
+<ports>
+<port protocol="tcp" portid="22"><state state="open" reason="syn-ack" reason_ttl="64"/><service name="ssh" method="probed" conf="10"><cpe>cpe:/a:openbsd:openssh</cpe><cpe>openssh 7.4</cpe></service></port>
+</ports>
+</host>
+</nmaprun>"#;
+        let results = crate::NmapResults::parse(xml).unwrap();
+        let (_, port) = results.iter_ports().next().unwrap();
+        let service = port.service_info.as_ref().unwrap();
+
+        assert_eq!(service.cpe.len(), 1);
+        assert_eq!(service.cpe[0].to_string(), "cpe:/a:openbsd:openssh");
+        assert_eq!(service.unparsed_cpe, vec!["openssh 7.4"]);
+    }
+
+    #[test]
+    fn wildcard_matching() {
+        let found: Cpe = "cpe:/a:openbsd:openssh:7.4".parse().unwrap();
+
+        let pattern: Cpe = "cpe:2.3:a:openbsd:openssh:*:*:*:*:*:*:*:*".parse().unwrap();
+        assert!(pattern.matches(&found));
+
+        let pattern: Cpe = "cpe:2.3:a:OpenBSD:openssh:7.?:*:*:*:*:*:*:*"
+            .parse()
+            .unwrap();
+        assert!(pattern.matches(&found));
+
+        let pattern: Cpe = "cpe:2.3:a:openbsd:openssh:7.4:*:*:*:*:*:*:*"
+            .parse()
+            .unwrap();
+        assert!(pattern.matches(&found));
+
+        //ANY in the found name is not a wildcard.
+        let pattern: Cpe = "cpe:2.3:a:openbsd:openssh:7.4:-:*:*:*:*:*:*"
+            .parse()
+            .unwrap();
+        assert!(!pattern.matches(&found));
+        let unversioned: Cpe = "cpe:/a:openbsd:openssh".parse().unwrap();
+        assert!(!found.matches(&unversioned));
+
+        let pattern: Cpe = "cpe:2.3:a:openbsd:openssh:6.*:*:*:*:*:*:*:*"
+            .parse()
+            .unwrap();
+        assert!(!pattern.matches(&found));
+
+        let pattern: Cpe = "cpe:/o:openbsd:openssh".parse().unwrap();
+        assert!(!pattern.matches(&found));
+
+        let pattern: Cpe = "cpe:/a".parse().unwrap();
+        assert!(pattern.matches(&found));
+
+        let pattern: Cpe = "cpe:/a:openbsd:openssh:7.%02".parse().unwrap();
+        assert_eq!(pattern.version, CpeValue::Pattern("7.*".to_string()));
+        assert!(pattern.matches(&found));
+    }
+
+    #[test]
+    fn quoted_wildcards_are_literal() {
+        let pattern: Cpe = "cpe:2.3:a:foo:bar\\*:*:*:*:*:*:*:*:*".parse().unwrap();
+        assert_eq!(pattern.product, CpeValue::Value("bar*".to_string()));
+        assert_eq!(pattern.to_cpe23(), "cpe:2.3:a:foo:bar\\*:*:*:*:*:*:*:*:*");
+        assert_eq!(pattern.to_uri(), "cpe:/a:foo:bar%2a");
+
+        let literal: Cpe = "cpe:/a:foo:bar%2a".parse().unwrap();
+        assert!(pattern.matches(&literal));
+        let other: Cpe = "cpe:/a:foo:barbaz".parse().unwrap();
+        assert!(!pattern.matches(&other));
+
+        let pattern: Cpe = "cpe:2.3:a:foo:b\\?r*:*:*:*:*:*:*:*:*".parse().unwrap();
+        assert_eq!(pattern.product, CpeValue::Pattern("b\\?r*".to_string()));
+        assert!(pattern.matches(&"cpe:/a:foo:b%3frx".parse().unwrap()));
+        assert!(!pattern.matches(&"cpe:/a:foo:barx".parse().unwrap()));
+    }
+
+    #[test]
+    fn wildcard_matching_is_not_exponential() {
+        let pattern = tokens(&"*a".repeat(30), true);
+        let value = "a".repeat(60).chars().chain(Some('b')).collect::<Vec<_>>();
+        assert!(!wildcard_match(&pattern, &value));
+    }
+}
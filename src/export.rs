@@ -0,0 +1,300 @@
+//!Export scan results for ingestion by other security tooling.
+//!
+//![`to_sarif()`](fn.to_sarif.html) produces a SARIF 2.1.0 log, in which
+//!open ports and vulnerability [findings](../vulns/index.html) are results
+//!and hosts are their locations. Results for findings are `error` level when
+//!confirmed vulnerable and `warning` level when likely vulnerable; findings
+//!in other states are omitted.
+//!
+//![`json_lines()`](fn.json_lines.html) produces one JSON event per host,
+//!port and script, for log pipelines that expect newline-delimited JSON.
+//!
+//!```
+//!# use std::path::PathBuf;
+//!# use std::fs;
+//!use nmap_xml_parser::{export, NmapResults};
+//!# let mut nmap_xml_file = PathBuf::new();
+//!# nmap_xml_file.push(&std::env::var("CARGO_MANIFEST_DIR").unwrap());
+//!# nmap_xml_file.push("tests/vulns.xml");
+//!let content = fs::read_to_string(nmap_xml_file).unwrap();
+//!let results = NmapResults::parse(&content).unwrap();
+//!
+//!let mut out = Vec::new();
+//!export::write_json_lines(&results, &mut out).unwrap();
+//!export::write_sarif(&results, &mut out).unwrap();
+//!```
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::net::IpAddr;
+
+use crate::host::{Address, Host, Script};
+use crate::port::{Port, PortState};
+use crate::vulns::{Finding, VulnState};
+use crate::NmapResults;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const OPEN_PORT_RULE: &str = "open-port";
+
+///Returns `results` as a SARIF 2.1.0 log.
+pub fn to_sarif(results: &NmapResults) -> Value {
+    let mut rules = BTreeMap::new();
+    rules.insert(
+        OPEN_PORT_RULE.to_string(),
+        json!({
+            "id": OPEN_PORT_RULE,
+            "shortDescription": { "text": "Open port" },
+        }),
+    );
+
+    let mut sarif_results = Vec::new();
+
+    for (host, port) in results.iter_ports() {
+        if port.status.state != PortState::Open {
+            continue;
+        }
+
+        let mut text = format!("Port {}/{} is open", port.port_number, port.protocol);
+        if let Some(service) = &port.service_info {
+            text.push_str(&format!(" ({})", service.name));
+        }
+
+        sarif_results.push(json!({
+            "ruleId": OPEN_PORT_RULE,
+            "level": "note",
+            "message": { "text": text },
+            "locations": [sarif_location(host, Some(port))],
+        }));
+    }
+
+    for finding in results.findings() {
+        let level = match finding.state {
+            VulnState::Vulnerable => "error",
+            VulnState::LikelyVulnerable => "warning",
+            VulnState::NotVulnerable | VulnState::Unknown => continue,
+        };
+
+        rules
+            .entry(finding.id.clone())
+            .or_insert_with(|| sarif_rule(&finding));
+
+        sarif_results.push(json!({
+            "ruleId": finding.id,
+            "level": level,
+            "message": { "text": finding.title.as_deref().unwrap_or(&finding.id) },
+            "locations": [sarif_location(finding.host, finding.port)],
+            "properties": {
+                "script": finding.script_id,
+                "cves": finding.cve_ids,
+            },
+        }));
+    }
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "nmap",
+                    "informationUri": "https://nmap.org/",
+                    "rules": rules.into_values().collect::<Vec<_>>(),
+                },
+            },
+            "results": sarif_results,
+        }],
+    })
+}
+
+///Writes `results` to `writer` as a SARIF 2.1.0 log.
+pub fn write_sarif<W: Write>(results: &NmapResults, writer: W) -> io::Result<()> {
+    serde_json::to_writer_pretty(writer, &to_sarif(results))?;
+    Ok(())
+}
+
+fn sarif_rule(finding: &Finding) -> Value {
+    let mut rule = json!({
+        "id": finding.id,
+        "shortDescription": { "text": finding.title.as_deref().unwrap_or(&finding.id) },
+    });
+
+    if let Some(uri) = finding.references.first() {
+        rule["helpUri"] = json!(uri);
+    }
+    if let Some(cvss) = finding.cvss {
+        //Code scanning dashboards rank rules on this property.
+        rule["properties"] = json!({ "security-severity": format!("{:.1}", cvss) });
+    }
+
+    rule
+}
+
+fn sarif_location(host: &Host, port: Option<&Port>) -> Value {
+    let address = host.display_address().unwrap_or_default();
+    let name = match port {
+        //IPv6 and MAC addresses contain colons, so they are bracketed to
+        //keep the port separator unambiguous.
+        Some(p) if address.contains(':') => {
+            format!("[{}]:{}/{}", address, p.port_number, p.protocol)
+        }
+        Some(p) => format!("{}:{}/{}", address, p.port_number, p.protocol),
+        None => address,
+    };
+
+    let mut location = json!({
+        "logicalLocations": [{
+            "name": name,
+            "kind": if port.is_some() { "port" } else { "host" },
+        }],
+    });
+    if let Some(uri) = sarif_uri(host, port) {
+        location["physicalLocation"] = json!({ "artifactLocation": { "uri": uri } });
+    }
+    location
+}
+
+///Returns a network-path reference to the first IP address of `host`, and
+///`port` if given, e.g. `//[2001:db8::1]:443` or `//[fe80::1%25eth0]`. MAC
+///addresses have no URI form, so hosts without an IP address have none.
+fn sarif_uri(host: &Host, port: Option<&Port>) -> Option<String> {
+    let address = host.addresses().find(|a| a.ip().is_some())?;
+
+    let mut uri = match (address, host.zone()) {
+        (Address::IpAddr(IpAddr::V4(ip)), _) => format!("//{}", ip),
+        //RFC 6874 requires the `%` before the zone index to be encoded.
+        (Address::IpAddr(IpAddr::V6(ip)), Some(zone)) => format!("//[{}%25{}]", ip, zone),
+        (Address::IpAddr(IpAddr::V6(ip)), None) => format!("//[{}]", ip),
+        (Address::MacAddr(_), _) => return None,
+    };
+    if let Some(port) = port {
+        uri.push_str(&format!(":{}", port.port_number));
+    }
+    Some(uri)
+}
+
+///Returns one JSON event per host, port and script in `results`.
+///
+///Every event has an `event` field of `host`, `port` or `script` and the
+///`address` of the host it belongs to. Port and script events also have the
+///`protocol` and `port` they belong to, which are null for host scripts.
+pub fn json_lines(results: &NmapResults) -> Vec<Value> {
+    let mut events = Vec::new();
+
+    for host in results.hosts() {
+        let address = host.display_address();
+
+        events.push(json!({
+            "event": "host",
+            "address": address,
+            "addresses": host.addresses().map(|a| host.address_value(a)).collect::<Vec<_>>(),
+            "hostnames": host.host_names().map(|h| h.name.as_str()).collect::<Vec<_>>(),
+            "state": host.status.state.to_string(),
+            "start_time": host.scan_start_time,
+            "end_time": host.scan_end_time,
+        }));
+
+        for script in host.scripts() {
+            events.push(script_event(&address, None, script));
+        }
+
+        for port in host.port_info.ports() {
+            let mut event = port_fields(&address, Some(port));
+            event.insert("event".to_string(), json!("port"));
+            event.insert("state".to_string(), json!(port.status.state.to_string()));
+            event.insert("reason".to_string(), json!(port.status.reason));
+            event.insert(
+                "service".to_string(),
+                json!(port.service_info.as_ref().map(|s| &s.name)),
+            );
+            events.push(Value::Object(event));
+
+            for script in &port.scripts {
+                events.push(script_event(&address, Some(port), script));
+            }
+        }
+    }
+
+    events
+}
+
+///Writes the events from [`json_lines()`](fn.json_lines.html) to `writer`,
+///one per line.
+pub fn write_json_lines<W: Write>(results: &NmapResults, mut writer: W) -> io::Result<()> {
+    for event in json_lines(results) {
+        serde_json::to_writer(&mut writer, &event)?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+fn script_event(address: &Option<String>, port: Option<&Port>, script: &Script) -> Value {
+    let mut event = port_fields(address, port);
+    event.insert("event".to_string(), json!("script"));
+    event.insert("script_id".to_string(), json!(script.id));
+    event.insert("output".to_string(), json!(script.output));
+    Value::Object(event)
+}
+
+fn port_fields(address: &Option<String>, port: Option<&Port>) -> Map<String, Value> {
+    let mut fields = Map::new();
+    fields.insert("address".to_string(), json!(address));
+    fields.insert(
+        "protocol".to_string(),
+        json!(port.map(|p| p.protocol.to_string())),
+    );
+    fields.insert("port".to_string(), json!(port.map(|p| p.port_number)));
+    fields
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sarif_uris() {
+        let xml = r#"<nmaprun start="1588318812">
+<host><status state="up" reason="user-set" reason_ttl="0"/>
+<address addr="2001:db8::1" addrtype="ipv6"/>
+<ports><port protocol="tcp" portid="443"><state state="open" reason="syn-ack" reason_ttl="64"/></port></ports>
+</host>
+<host><status state="up" reason="user-set" reason_ttl="0"/>
+<address addr="00:00:5E:00:53:01" addrtype="mac"/>
+<ports><port protocol="tcp" portid="22"><state state="open" reason="syn-ack" reason_ttl="64"/></port></ports>
+</host>
+<host><status state="up" reason="user-set" reason_ttl="0"/>
+<address addr="fe80::1%eth0" addrtype="ipv6"/>
+<ports><port protocol="tcp" portid="22"><state state="open" reason="syn-ack" reason_ttl="64"/></port></ports>
+</host>
+</nmaprun>"#;
+        let results = NmapResults::parse(xml).unwrap();
+        let sarif = to_sarif(&results);
+        let locations = sarif["runs"][0]["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| &r["locations"][0])
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            locations[0]["physicalLocation"]["artifactLocation"]["uri"],
+            "//[2001:db8::1]:443"
+        );
+        assert_eq!(
+            locations[0]["logicalLocations"][0]["name"],
+            "[2001:db8::1]:443/tcp"
+        );
+        assert!(locations[1].get("physicalLocation").is_none());
+        assert_eq!(
+            locations[1]["logicalLocations"][0]["name"],
+            "[00:00:5E:00:53:01]:22/tcp"
+        );
+        assert_eq!(
+            locations[2]["physicalLocation"]["artifactLocation"]["uri"],
+            "//[fe80::1%25eth0]:22"
+        );
+        assert_eq!(
+            locations[2]["logicalLocations"][0]["name"],
+            "[fe80::1%eth0]:22/tcp"
+        );
+    }
+}
@@ -0,0 +1,379 @@
+//!History of hosts across a series of scans.
+//!
+//!```
+//!# use std::path::PathBuf;
+//!# use std::fs;
+//!use nmap_xml_parser::NmapResults;
+//!use nmap_xml_parser::history::History;
+//!use nmap_xml_parser::port::PortProtocol;
+//!# let mut nmap_xml_file = PathBuf::new();
+//!# nmap_xml_file.push(&std::env::var("CARGO_MANIFEST_DIR").unwrap());
+//!# nmap_xml_file.push("tests/scanme-version.xml");
+//!let content = fs::read_to_string(nmap_xml_file).unwrap();
+//!let results = NmapResults::parse(&content).unwrap();
+//!
+//!let history = History::new(&[results]);
+//!let ssh = history
+//!    .get("45.33.32.156".parse().unwrap())
+//!    .and_then(|t| t.port(PortProtocol::Tcp, 22))
+//!    .unwrap();
+//!println!("22/tcp first seen open at {:?}", ssh.first_seen_open);
+//!```
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+use crate::host::{Host, HostState};
+use crate::port::{Port, PortProtocol, PortState};
+use crate::report::service_version;
+use crate::NmapResults;
+
+///A value that was observed at `time` and did not change until the next
+///change in the list.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Change<T> {
+    ///Start time of the scan the value was first observed in, as seconds
+    ///since the Unix epoch.
+    pub time: i64,
+    pub value: T,
+}
+
+///The timelines of all hosts in a series of scans.
+#[derive(Clone, Debug, Default)]
+pub struct History {
+    timelines: Vec<HostTimeline>,
+    index: HashMap<IpAddr, usize>,
+}
+
+impl History {
+    ///Builds the history of the hosts in `scans`. Scans are processed in
+    ///order of their start time, whatever their order in `scans`.
+    pub fn new<'a, I>(scans: I) -> Self
+    where
+        I: IntoIterator<Item = &'a NmapResults>,
+    {
+        let mut scans = scans.into_iter().collect::<Vec<_>>();
+        scans.sort_by_key(|s| s.scan_start_time);
+
+        let mut history = History::default();
+        for scan in scans {
+            for host in scan.hosts() {
+                history.add_host(scan.scan_start_time, host);
+            }
+        }
+        history
+    }
+
+    ///Returns an iterator over the timelines, in order of the first scan
+    ///each host was seen in.
+    pub fn timelines(&self) -> std::slice::Iter<'_, HostTimeline> {
+        self.timelines.iter()
+    }
+
+    ///Returns the timeline of the host with IP address `ip`. Hosts are
+    ///identified by the IP address of their
+    ///[`Host::key()`](../host/struct.Host.html#method.key) alone, as Nmap
+    ///only reports the MAC address of hosts that are up and were scanned
+    ///with privileges. Hosts without an IP address are not tracked.
+    pub fn get(&self, ip: IpAddr) -> Option<&HostTimeline> {
+        self.index.get(&ip).map(|i| &self.timelines[*i])
+    }
+
+    fn add_host(&mut self, time: i64, host: &Host) {
+        let key = match host.key() {
+            Some(key) => key,
+            None => return,
+        };
+
+        let timelines = &mut self.timelines;
+        let i = *self.index.entry(key.ip).or_insert_with(|| {
+            timelines.push(HostTimeline::new(key.ip));
+            timelines.len() - 1
+        });
+        if key.mac.is_some() {
+            self.timelines[i].mac = key.mac;
+        }
+        self.timelines[i].add(time, host);
+    }
+}
+
+///What was observed of one host across a series of scans.
+#[derive(Clone, Debug)]
+pub struct HostTimeline {
+    pub ip: IpAddr,
+    ///The MAC address last reported for the host, if any.
+    pub mac: Option<String>,
+    ///Start time of the first scan the host was up in.
+    pub first_seen: Option<i64>,
+    ///Start time of the last scan the host was up in.
+    pub last_seen: Option<i64>,
+    ///Changes of the host's state, starting with the first scan it was in.
+    pub states: Vec<Change<HostState>>,
+    ///Changes of the host's names, with duplicates removed as by
+    ///[`Host::unique_names()`](../host/struct.Host.html#method.unique_names).
+    ///Scans in which the host was not up and reported no names are skipped,
+    ///as Nmap does not resolve the names of hosts that are down.
+    pub host_names: Vec<Change<Vec<String>>>,
+    ports: Vec<PortHistory>,
+}
+
+impl HostTimeline {
+    fn new(ip: IpAddr) -> Self {
+        HostTimeline {
+            ip,
+            mac: None,
+            first_seen: None,
+            last_seen: None,
+            states: Vec::new(),
+            host_names: Vec::new(),
+            ports: Vec::new(),
+        }
+    }
+
+    ///Returns an iterator over the histories of the ports of the host, in
+    ///order of the first scan each port was listed in.
+    pub fn ports(&self) -> std::slice::Iter<'_, PortHistory> {
+        self.ports.iter()
+    }
+
+    ///Returns the history of a port of the host, if it was ever listed.
+    pub fn port(&self, protocol: PortProtocol, port_number: u16) -> Option<&PortHistory> {
+        self.ports
+            .iter()
+            .find(|p| p.protocol == protocol && p.port_number == port_number)
+    }
+
+    fn add(&mut self, time: i64, host: &Host) {
+        if host.status.state == HostState::Up {
+            self.first_seen.get_or_insert(time);
+            self.last_seen = Some(time);
+        }
+        push_change(&mut self.states, time, host.status.state.clone());
+
+        let names = host
+            .unique_names()
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        if host.status.state == HostState::Up || !names.is_empty() {
+            push_change(&mut self.host_names, time, names);
+        }
+
+        for port in host.port_info.ports() {
+            let i = match self
+                .ports
+                .iter()
+                .position(|p| p.protocol == port.protocol && p.port_number == port.port_number)
+            {
+                Some(i) => i,
+                None => {
+                    self.ports.push(PortHistory::new(port));
+                    self.ports.len() - 1
+                }
+            };
+            self.ports[i].add(time, port);
+        }
+    }
+}
+
+///What was observed of one port across a series of scans.
+///
+///Only scans that listed the port are taken into account. Nmap does not list
+///ports in the most common state of a host, so e.g. a port that closed may
+///not appear to change state.
+#[derive(Clone, Debug)]
+pub struct PortHistory {
+    pub protocol: PortProtocol,
+    pub port_number: u16,
+    ///Start time of the first scan the port was open in.
+    pub first_seen_open: Option<i64>,
+    ///Start time of the last scan the port was open in.
+    pub last_seen_open: Option<i64>,
+    ///Changes of the port's state, starting with the first scan it was
+    ///listed in.
+    pub states: Vec<Change<PortState>>,
+    ///Changes of the product and version detected on the port, as Nmap
+    ///prints them in the VERSION column. Scans that did not detect a version
+    ///on the port, e.g. because version detection was not enabled, are
+    ///skipped.
+    pub versions: Vec<Change<String>>,
+}
+
+impl PortHistory {
+    fn new(port: &Port) -> Self {
+        PortHistory {
+            protocol: port.protocol.clone(),
+            port_number: port.port_number,
+            first_seen_open: None,
+            last_seen_open: None,
+            states: Vec::new(),
+            versions: Vec::new(),
+        }
+    }
+
+    ///Returns the number of times the state of the port changed.
+    pub fn flaps(&self) -> usize {
+        self.states.len().saturating_sub(1)
+    }
+
+    fn add(&mut self, time: i64, port: &Port) {
+        if port.status.state == PortState::Open {
+            self.first_seen_open.get_or_insert(time);
+            self.last_seen_open = Some(time);
+        }
+        push_change(&mut self.states, time, port.status.state.clone());
+
+        if let Some(version) = port.service_info.as_ref().and_then(service_version) {
+            push_change(&mut self.versions, time, version);
+        }
+    }
+}
+
+///Adds `value` to `changes` if it differs from the last value.
+fn push_change<T: PartialEq>(changes: &mut Vec<Change<T>>, time: i64, value: T) {
+    if changes.last().map(|c| &c.value) != Some(&value) {
+        changes.push(Change { time, value });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn scan(start: i64, hosts: &str) -> NmapResults {
+        NmapResults::parse(&format!("<nmaprun start=\"{}\">{}</nmaprun>", start, hosts)).unwrap()
+    }
+
+    fn host(name: &str, ports: &str) -> String {
+        format!(
+            r#"<host><status state="up" reason="echo-reply" reason_ttl="53"/>
+<address addr="192.0.2.1" addrtype="ipv4"/>
+<hostnames><hostname name="{}" type="PTR"/></hostnames>
+<ports>{}</ports>
+</host>"#,
+            name, ports
+        )
+    }
+
+    fn port(number: u16, state: &str, version: &str) -> String {
+        format!(
+            r#"<port protocol="tcp" portid="{}"><state state="{}" reason="syn-ack" reason_ttl="53"/><service name="x" product="x" version="{}" method="probed" conf="10"/></port>"#,
+            number, state, version
+        )
+    }
+
+    #[test]
+    fn timeline() {
+        let scans = vec![
+            scan(300, &host("b.example", &port(3389, "open", "2"))),
+            scan(100, &host("a.example", &port(22, "open", "1"))),
+            scan(
+                200,
+                &host(
+                    "a.example",
+                    &(port(22, "filtered", "1") + &port(3389, "open", "1")),
+                ),
+            ),
+            scan(
+                400,
+                r#"<host><status state="down" reason="no-response" reason_ttl="0"/><address addr="192.0.2.1" addrtype="ipv4"/></host>"#,
+            ),
+            scan(
+                500,
+                &host(
+                    "b.example",
+                    r#"<port protocol="tcp" portid="3389"><state state="open" reason="syn-ack" reason_ttl="53"/></port>"#,
+                ),
+            ),
+        ];
+        let history = History::new(&scans);
+
+        assert_eq!(history.timelines().len(), 1);
+        let timeline = history.get("192.0.2.1".parse().unwrap()).unwrap();
+        assert_eq!(timeline.first_seen, Some(100));
+        assert_eq!(timeline.last_seen, Some(500));
+        assert_eq!(
+            timeline.states,
+            vec![
+                Change {
+                    time: 100,
+                    value: HostState::Up
+                },
+                Change {
+                    time: 400,
+                    value: HostState::Down
+                },
+                Change {
+                    time: 500,
+                    value: HostState::Up
+                },
+            ]
+        );
+        let names = timeline
+            .host_names
+            .iter()
+            .map(|c| (c.time, c.value.join(",")))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                (100, "a.example".to_string()),
+                (300, "b.example".to_string()),
+            ]
+        );
+
+        let ports = timeline.ports().map(|p| p.port_number).collect::<Vec<_>>();
+        assert_eq!(ports, vec![22, 3389]);
+
+        let ssh = timeline.port(PortProtocol::Tcp, 22).unwrap();
+        assert_eq!(ssh.first_seen_open, Some(100));
+        assert_eq!(ssh.last_seen_open, Some(100));
+        assert_eq!(ssh.flaps(), 1);
+        assert_eq!(ssh.states[1].value, PortState::Filtered);
+
+        let rdp = timeline.port(PortProtocol::Tcp, 3389).unwrap();
+        assert_eq!(rdp.first_seen_open, Some(200));
+        assert_eq!(rdp.last_seen_open, Some(500));
+        assert_eq!(rdp.flaps(), 0);
+        let versions = rdp
+            .versions
+            .iter()
+            .map(|c| (c.time, c.value.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(versions, vec![(200, "x 1"), (300, "x 2")]);
+    }
+
+    #[test]
+    fn lan_host_without_mac_when_down() {
+        let up = |start| {
+            scan(
+                start,
+                r#"<host><status state="up" reason="arp-response" reason_ttl="0"/>
+<address addr="192.168.1.20" addrtype="ipv4"/>
+<address addr="00:00:5E:00:53:01" addrtype="mac"/>
+</host>"#,
+            )
+        };
+        let down = scan(
+            200,
+            r#"<host><status state="down" reason="no-response" reason_ttl="0"/><address addr="192.168.1.20" addrtype="ipv4"/></host>"#,
+        );
+        let history = History::new(&[up(100), down, up(300)]);
+
+        assert_eq!(history.timelines().len(), 1);
+        let timeline = history.get("192.168.1.20".parse().unwrap()).unwrap();
+        assert_eq!(timeline.mac.as_deref(), Some("00:00:5E:00:53:01"));
+        let states = timeline
+            .states
+            .iter()
+            .map(|c| (c.time, c.value.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            states,
+            vec![
+                (100, HostState::Up),
+                (200, HostState::Down),
+                (300, HostState::Up)
+            ]
+        );
+    }
+}
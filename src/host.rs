@@ -1,131 +1,440 @@
 //!Host related structs and enums.
 use roxmltree::Node;
+use std::borrow::Cow;
+use std::fmt;
 use std::net::IpAddr;
-use std::str::FromStr;
+use std::path::{Path, PathBuf};
 use strum_macros::{Display, EnumString};
 
-use crate::port::PortInfo;
-use crate::Error;
+use crate::coverage::{host_coverage, Coverage};
+use crate::os::{parse_os_node, OsMatch};
+use crate::parse::{
+    attribute, borrowed_attribute, missing_element, parse_attribute, parse_optional_attribute,
+};
+use crate::port::{PortInfo, PortInfoRef};
+use crate::raw::Unrecognized;
+use crate::{Error, Location, ParseContext, ScanInfo};
 
-#[derive(Display, Clone, Debug, PartialEq)]
+#[derive(Display, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Address {
     IpAddr(IpAddr),
     MacAddr(String),
 }
 
+impl Address {
+    ///Returns the address itself as a string, e.g. `192.0.2.1`.
+    pub(crate) fn value(&self) -> String {
+        match self {
+            Address::IpAddr(ip) => ip.to_string(),
+            Address::MacAddr(mac) => mac.clone(),
+        }
+    }
+
+    ///Returns the IP address, or `None` for a MAC address.
+    pub(crate) fn ip(&self) -> Option<IpAddr> {
+        match self {
+            Address::IpAddr(ip) => Some(*ip),
+            Address::MacAddr(_) => None,
+        }
+    }
+}
+
+///The identity of a host, for use as a map key when deduplicating,
+///merging or comparing hosts across scans.
+///
+///The key of a host is derived from its addresses as follows:
+///
+///- `ip` is the first IP address of the host, in document order. This is
+///  the address Nmap scanned, so hosts without one have no key.
+///- `mac` is the first MAC address of the host, in upper case as Nmap
+///  writes it. Nmap only learns MAC addresses of hosts on the same link, so
+///  this is `None` for other hosts.
+///
+///Keys are equal if both their IP and MAC addresses are equal. A host
+///scanned from a different network segment has a different key, so compare
+///the `ip` fields alone when combining scans from several vantage points.
+///The zone index of scoped IPv6 addresses is not part of the key, so
+///link-local hosts on different links can have the same key.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct HostKey {
+    pub ip: IpAddr,
+    pub mac: Option<String>,
+}
+
+impl HostKey {
+    fn from_addresses(addresses: &[Address]) -> Option<Self> {
+        let ip = addresses.iter().find_map(Address::ip)?;
+        let mac = addresses.iter().find_map(|a| match a {
+            Address::MacAddr(mac) => Some(mac.to_ascii_uppercase()),
+            _ => None,
+        });
+
+        Some(HostKey { ip, mac })
+    }
+}
+
+///Formats the key as e.g. `192.0.2.1` or `192.0.2.1 (00:00:5E:00:53:01)`.
+impl fmt::Display for HostKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.mac {
+            Some(mac) => write!(f, "{} ({})", self.ip, mac),
+            None => write!(f, "{}", self.ip),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Host {
     pub(crate) addresses: Vec<Address>,
+    pub(crate) zone: Option<String>,
     pub(crate) scripts: Vec<Script>,
     pub status: HostStatus,
     pub(crate) host_names: Vec<Hostname>,
     pub port_info: PortInfo,
+    pub(crate) os_matches: Vec<OsMatch>,
+    pub times: Option<HostTimes>,
     pub scan_start_time: Option<i64>,
     pub scan_end_time: Option<i64>,
+    pub(crate) unrecognized: Unrecognized,
+    pub(crate) source: Option<PathBuf>,
 }
 
 impl Host {
-    pub(crate) fn parse(node: Node) -> Result<Self, Error> {
-        let scan_start_time = node
-            .attribute("starttime")
-            .map(|s| {
-                s.parse::<i64>()
-                    .map_err(|_| Error::from("failed to parse host start time"))
-            })
-            .transpose()?;
+    pub(crate) fn parse(node: Node, ctx: &mut ParseContext) -> Result<Self, Error> {
+        HostRef::parse(node, ctx).map(HostRef::into_owned)
+    }
 
-        let scan_end_time = node
-            .attribute("endtime")
-            .map(|s| {
-                s.parse::<i64>()
-                    .map_err(|_| Error::from("failed to parse host end time"))
+    ///Returns an iterator over the addresses associated with this host.
+    pub fn addresses(&self) -> std::slice::Iter<'_, Address> {
+        self.addresses.iter()
+    }
+
+    ///Returns the zone index of the host's scoped IPv6 address, e.g. `eth0`
+    ///for `fe80::1%eth0`. The address itself is in
+    ///[`addresses()`](#method.addresses) without it.
+    pub fn zone(&self) -> Option<&str> {
+        self.zone.as_deref()
+    }
+
+    ///Returns an iterator over the scripts associated with this host.
+    pub fn scripts(&self) -> std::slice::Iter<'_, Script> {
+        self.scripts.iter()
+    }
+
+    ///Returns an iterator over the names associated with this host.
+    pub fn host_names(&self) -> std::slice::Iter<'_, Hostname> {
+        self.host_names.iter()
+    }
+
+    ///Returns the name that best identifies this host.
+    ///
+    ///This is the name given on the command line if there is one, otherwise
+    ///the first reverse DNS name, otherwise the first name of any kind.
+    pub fn primary_name(&self) -> Option<&str> {
+        self.host_names
+            .iter()
+            .find(|h| h.source == HostnameType::User)
+            .or_else(|| {
+                self.host_names
+                    .iter()
+                    .find(|h| h.source == HostnameType::Dns)
             })
-            .transpose()?;
+            .or_else(|| self.host_names.first())
+            .map(|h| h.name.as_str())
+    }
+
+    ///Returns the names associated with this host with duplicates removed.
+    ///
+    ///Names are compared case-insensitively and without any trailing dot.
+    ///The first occurrence of each name is kept, in document order.
+    pub fn unique_names(&self) -> Vec<&str> {
+        let mut r: Vec<&str> = Vec::new();
+        for h in &self.host_names {
+            if !r.iter().any(|n| names_eq(n, &h.name)) {
+                r.push(&h.name);
+            }
+        }
+        r
+    }
+
+    ///Returns true if the host has both user-supplied and reverse DNS names
+    ///and none of the reverse DNS names match a user-supplied one.
+    ///
+    ///This usually means the forward lookup of the target name and the
+    ///reverse lookup of its address disagree.
+    pub fn has_forward_reverse_mismatch(&self) -> bool {
+        let names_of = |source: HostnameType| {
+            self.host_names
+                .iter()
+                .filter(move |h| h.source == source)
+                .map(|h| h.name.as_str())
+        };
+
+        let mut forward = names_of(HostnameType::User).peekable();
+        let reverse = names_of(HostnameType::Dns).collect::<Vec<_>>();
+        if forward.peek().is_none() || reverse.is_empty() {
+            return false;
+        }
+
+        !forward.any(|f| reverse.iter().any(|r| names_eq(f, r)))
+    }
+
+    ///Returns the first IP address of this host, or its MAC address if it
+    ///has none.
+    pub(crate) fn display_address(&self) -> Option<String> {
+        self.addresses
+            .iter()
+            .find(|a| a.ip().is_some())
+            .or_else(|| self.addresses.first())
+            .map(|a| self.address_value(a))
+    }
+
+    ///Returns `address` as a string, with the zone index of the host
+    ///appended to IPv6 addresses, e.g. `fe80::1%eth0`.
+    pub(crate) fn address_value(&self, address: &Address) -> String {
+        match (address, &self.zone) {
+            (Address::IpAddr(IpAddr::V6(ip)), Some(zone)) => format!("{}%{}", ip, zone),
+            _ => address.value(),
+        }
+    }
+
+    ///Returns the identity of this host, or `None` if it has no IP address.
+    ///See [`HostKey`](struct.HostKey.html).
+    pub fn key(&self) -> Option<HostKey> {
+        HostKey::from_addresses(&self.addresses)
+    }
+
+    ///Returns which ports of the host were scanned by `scan_info`, and in
+    ///which state, for each protocol scanned. See
+    ///[`NmapResults::coverage()`](../struct.NmapResults.html#method.coverage).
+    pub fn coverage<'a, I>(&self, scan_info: I) -> Vec<Coverage>
+    where
+        I: IntoIterator<Item = &'a ScanInfo>,
+    {
+        host_coverage(self, &scan_info.into_iter().collect::<Vec<_>>())
+    }
+
+    ///Returns content of the `host` element that is not otherwise modeled.
+    pub fn unrecognized(&self) -> &Unrecognized {
+        &self.unrecognized
+    }
+
+    ///Returns an iterator over the OS detection matches for this host, in
+    ///the order reported by Nmap.
+    pub fn os_matches(&self) -> std::slice::Iter<'_, OsMatch> {
+        self.os_matches.iter()
+    }
+
+    ///Returns the file this host was loaded from, if it is part of a
+    ///[`ScanCollection`](../collection/struct.ScanCollection.html).
+    pub fn source(&self) -> Option<&Path> {
+        self.source.as_deref()
+    }
+}
+
+///A [`Host`](struct.Host.html) whose names, port details and script output
+///are borrowed from the input where possible. Part of
+///[`NmapResultsRef`](../struct.NmapResultsRef.html).
+#[derive(Clone, Debug)]
+pub struct HostRef<'a> {
+    pub(crate) addresses: Vec<Address>,
+    pub(crate) zone: Option<String>,
+    pub(crate) scripts: Vec<ScriptRef<'a>>,
+    pub status: HostStatus,
+    pub(crate) host_names: Vec<HostnameRef<'a>>,
+    pub port_info: PortInfoRef<'a>,
+    pub(crate) os_matches: Vec<OsMatch>,
+    pub times: Option<HostTimes>,
+    pub scan_start_time: Option<i64>,
+    pub scan_end_time: Option<i64>,
+    pub(crate) unrecognized: Unrecognized,
+}
+
+impl<'a> HostRef<'a> {
+    pub(crate) fn parse(node: Node<'_, 'a>, ctx: &mut ParseContext) -> Result<Self, Error> {
+        let scan_start_time = parse_optional_attribute(node, "starttime");
+        let scan_start_time = ctx.or_default(node, scan_start_time, || None)?;
+
+        let scan_end_time = parse_optional_attribute(node, "endtime");
+        let scan_end_time = ctx.or_default(node, scan_end_time, || None)?;
+
+        let mut unrecognized = ctx.unrecognized(node, &["starttime", "endtime"]);
 
         let mut status = None;
         let mut host_names = Vec::new();
         let mut port_info = Default::default();
         let mut scripts = Vec::new();
         let mut addresses = Vec::new();
+        let mut zone = None;
+        let mut os_matches = Vec::new();
+        let mut times = None;
 
         for child in node.children() {
             match child.tag_name().name() {
-                "address" => addresses.push(parse_address_node(child)?),
-                "status" => status = Some(HostStatus::parse(child)?),
-                "hostnames" => host_names = parse_hostnames_node(child)?,
-                "hostscript" => scripts = parse_hostscript_node(child)?,
-                "ports" => port_info = PortInfo::parse(child)?,
-                _ => {}
+                "address" => {
+                    let address = parse_address_node(child);
+                    if let Some((address, scope)) = ctx.or_skip(child, address)? {
+                        addresses.push(address);
+                        zone = zone.or(scope);
+                    }
+                }
+                "status" => status = Some(HostStatus::parse(child, ctx)?),
+                "hostnames" => host_names = parse_hostnames_node(child, ctx)?,
+                "hostscript" => scripts = parse_hostscript_node(child, ctx)?,
+                "ports" => port_info = PortInfoRef::parse(child, ctx)?,
+                "os" => os_matches = parse_os_node(child, ctx)?,
+                "times" => {
+                    let t = HostTimes::parse(child);
+                    times = ctx.or_skip(child, t)?;
+                }
+                _ => ctx.preserve(&mut unrecognized, child),
             }
         }
 
-        let status = status.ok_or_else(|| Error::from("expected `status` node for host"))?;
+        let status = status.ok_or_else(|| missing_element(node, "status"))?;
 
-        Ok(Host {
+        Ok(HostRef {
             addresses,
+            zone,
             scripts,
             status,
             host_names,
             port_info,
+            os_matches,
+            times,
             scan_start_time,
             scan_end_time,
+            unrecognized,
         })
     }
 
     ///Returns an iterator over the addresses associated with this host.
-    pub fn addresses(&self) -> std::slice::Iter<Address> {
+    pub fn addresses(&self) -> std::slice::Iter<'_, Address> {
         self.addresses.iter()
     }
 
+    ///Returns the zone index of the host's scoped IPv6 address. See
+    ///[`Host::zone()`](struct.Host.html#method.zone).
+    pub fn zone(&self) -> Option<&str> {
+        self.zone.as_deref()
+    }
+
+    ///Returns the identity of this host, or `None` if it has no IP address.
+    ///See [`HostKey`](struct.HostKey.html).
+    pub fn key(&self) -> Option<HostKey> {
+        HostKey::from_addresses(&self.addresses)
+    }
+
     ///Returns an iterator over the scripts associated with this host.
-    pub fn scripts(&self) -> std::slice::Iter<Script> {
+    pub fn scripts(&self) -> std::slice::Iter<'_, ScriptRef<'a>> {
         self.scripts.iter()
     }
 
     ///Returns an iterator over the names associated with this host.
-    pub fn host_names(&self) -> std::slice::Iter<Hostname> {
+    pub fn host_names(&self) -> std::slice::Iter<'_, HostnameRef<'a>> {
         self.host_names.iter()
     }
-}
 
-fn parse_address_node(node: Node) -> Result<Address, Error> {
-    let addrtype = node
-        .attribute("addrtype")
-        .ok_or_else(|| Error::from("expected `addrtype` attribute in `address` node"))?;
+    ///Returns an iterator over the OS detection matches for this host, in
+    ///the order reported by Nmap.
+    pub fn os_matches(&self) -> std::slice::Iter<'_, OsMatch> {
+        self.os_matches.iter()
+    }
+
+    ///Returns content of the `host` element that is not otherwise modeled.
+    pub fn unrecognized(&self) -> &Unrecognized {
+        &self.unrecognized
+    }
+
+    ///Copies the host into a [`Host`](struct.Host.html).
+    pub fn to_owned(&self) -> Host {
+        self.clone().into_owned()
+    }
 
-    let addr = node
-        .attribute("addr")
-        .ok_or_else(|| Error::from("expected `addr` attribute in `address` node"))?;
+    ///Converts the host into a [`Host`](struct.Host.html), copying only the
+    ///strings that are still borrowed.
+    pub fn into_owned(self) -> Host {
+        Host {
+            addresses: self.addresses,
+            zone: self.zone,
+            scripts: self
+                .scripts
+                .into_iter()
+                .map(ScriptRef::into_owned)
+                .collect(),
+            status: self.status,
+            host_names: self
+                .host_names
+                .into_iter()
+                .map(HostnameRef::into_owned)
+                .collect(),
+            port_info: self.port_info.into_owned(),
+            os_matches: self.os_matches,
+            times: self.times,
+            scan_start_time: self.scan_start_time,
+            scan_end_time: self.scan_end_time,
+            unrecognized: self.unrecognized,
+            source: None,
+        }
+    }
+}
+
+pub(crate) fn names_eq(a: &str, b: &str) -> bool {
+    a.trim_end_matches('.')
+        .eq_ignore_ascii_case(b.trim_end_matches('.'))
+}
 
-    match addrtype {
-        "mac" => Ok(Address::MacAddr(addr.to_string())),
+///Parses an `address` element into the address and, for a scoped IPv6
+///address such as `fe80::1%eth0`, its zone index.
+fn parse_address_node(node: Node) -> Result<(Address, Option<String>), Error> {
+    match attribute(node, "addrtype")? {
+        "mac" => Ok((Address::MacAddr(attribute(node, "addr")?.to_string()), None)),
         _ => {
-            let a = addr
-                .parse::<IpAddr>()
-                .map_err(|_| Error::from("failed to parse IP address"))?;
-            Ok(Address::IpAddr(a))
+            let addr = attribute(node, "addr")?;
+            let (ip, zone) = match addr.find('%') {
+                Some(i) if node.attribute("addrtype") == Some("ipv6") => {
+                    (&addr[..i], Some(addr[i + 1..].to_string()))
+                }
+                _ => (addr, None),
+            };
+            match ip.parse() {
+                Ok(ip) => Ok((Address::IpAddr(ip), zone)),
+                Err(_) => Err(Error::InvalidAttribute {
+                    location: Location::of_attribute(node, "addr"),
+                    attribute: "addr",
+                    value: addr.to_string(),
+                }),
+            }
         }
     }
 }
 
-fn parse_hostscript_node(node: Node) -> Result<Vec<Script>, Error> {
+fn parse_hostscript_node<'a>(
+    node: Node<'_, 'a>,
+    ctx: &mut ParseContext,
+) -> Result<Vec<ScriptRef<'a>>, Error> {
     let mut r = Vec::new();
 
     for child in node.children() {
         if child.tag_name().name() == "script" {
-            r.push(Script::parse(child)?);
+            let script = ScriptRef::parse(child, ctx);
+            r.extend(ctx.or_skip(child, script)?);
         }
     }
 
     Ok(r)
 }
 
-fn parse_hostnames_node(node: Node) -> Result<Vec<Hostname>, Error> {
+fn parse_hostnames_node<'a>(
+    node: Node<'_, 'a>,
+    ctx: &mut ParseContext,
+) -> Result<Vec<HostnameRef<'a>>, Error> {
     let mut r = Vec::new();
 
     for child in node.children() {
         if child.tag_name().name() == "hostname" {
-            r.push(Hostname::parse(child)?);
+            let hostname = HostnameRef::parse(child);
+            r.extend(ctx.or_skip(child, hostname)?);
         }
     }
 
@@ -137,38 +446,64 @@ pub struct HostStatus {
     pub state: HostState,
     pub reason: String,
     pub reason_ttl: u8,
+    pub(crate) unrecognized: Unrecognized,
 }
 
 impl HostStatus {
-    fn parse(node: Node) -> Result<Self, Error> {
-        let s = node
-            .attribute("state")
-            .ok_or_else(|| Error::from("expected `state` attribute in `hoststatus` node"))?;
-        let state =
-            HostState::from_str(s).map_err(|_| Error::from("failed to parse host state"))?;
-
-        let reason = node
-            .attribute("reason")
-            .ok_or_else(|| Error::from("expected `reason` attribute in `hoststatus` node"))?
-            .to_string();
-
-        let reason_ttl = node
-            .attribute("reason_ttl")
-            .ok_or_else(|| Error::from("expected `reason_ttl` attribute in `hoststatus` node"))
-            .and_then(|s| {
-                s.parse::<u8>()
-                    .map_err(|_| Error::from("failed to parse `reason_ttl`"))
-            })?;
+    fn parse(node: Node, ctx: &mut ParseContext) -> Result<Self, Error> {
+        let mut unrecognized = ctx.unrecognized(node, &["state", "reason", "reason_ttl"]);
+        for child in node.children() {
+            ctx.preserve(&mut unrecognized, child);
+        }
+
+        let state = parse_attribute(node, "state");
+        let state = ctx.or_default(node, state, || match node.attribute("state") {
+            Some(raw) => HostState::Other(raw.to_string()),
+            None => HostState::Unknown,
+        })?;
+
+        let reason = attribute(node, "reason")?.to_string();
+
+        let reason_ttl = parse_attribute(node, "reason_ttl");
+        let reason_ttl = ctx.or_default(node, reason_ttl, || 0)?;
 
         Ok(HostStatus {
             state,
             reason,
             reason_ttl,
+            unrecognized,
+        })
+    }
+
+    ///Returns content of the `status` element that is not otherwise modeled.
+    ///Empty unless
+    ///[`ParseOptions::preserve_unknown`](../struct.ParseOptions.html) is set.
+    pub fn unrecognized(&self) -> &Unrecognized {
+        &self.unrecognized
+    }
+}
+
+///Round-trip timing Nmap measured for a host, in microseconds.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HostTimes {
+    ///Smoothed round-trip time, or -1 if none was measured.
+    pub srtt: i64,
+    pub rttvar: i64,
+    ///Probe timeout.
+    pub timeout: i64,
+}
+
+impl HostTimes {
+    fn parse(node: Node) -> Result<Self, Error> {
+        Ok(HostTimes {
+            srtt: parse_attribute(node, "srtt")?,
+            rttvar: parse_attribute(node, "rttvar")?,
+            timeout: parse_attribute(node, "to")?,
         })
     }
 }
 
-#[derive(EnumString, Display, Clone, Debug, PartialEq)]
+#[derive(EnumString, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum HostState {
     #[strum(serialize = "up")]
     Up,
@@ -178,14 +513,47 @@ pub enum HostState {
     Unknown,
     #[strum(serialize = "skipped")]
     Skipped,
+    ///A `state` value Nmap does not write, kept verbatim. Only produced in
+    ///lenient mode; strict parsing rejects such values.
+    #[strum(disabled = "true")]
+    Other(String),
+}
+
+impl fmt::Display for HostState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            HostState::Up => "up",
+            HostState::Down => "down",
+            HostState::Unknown => "unknown",
+            HostState::Skipped => "skipped",
+            HostState::Other(s) => s,
+        };
+        write!(f, "{}", s)
+    }
 }
 
-#[derive(EnumString, Display, Clone, Debug, PartialEq)]
+///Where Nmap learned a host name from.
+#[derive(EnumString, Clone, Debug, PartialEq)]
 pub enum HostnameType {
-    #[strum(serialize = "user", to_string = "User")]
+    ///The name was given on the command line as a target.
+    #[strum(serialize = "user")]
     User,
-    #[strum(serialize = "PTR", to_string = "Dns")]
+    ///The name came from a reverse DNS (PTR) lookup.
+    #[strum(serialize = "PTR")]
     Dns,
+    ///Any other `type` value, kept verbatim.
+    #[strum(default = "true")]
+    Other(String),
+}
+
+impl fmt::Display for HostnameType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HostnameType::User => write!(f, "User"),
+            HostnameType::Dns => write!(f, "Dns"),
+            HostnameType::Other(s) => write!(f, "{}", s),
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -194,45 +562,185 @@ pub struct Hostname {
     pub source: HostnameType,
 }
 
-impl Hostname {
-    fn parse(node: Node) -> Result<Self, Error> {
-        let name = node
-            .attribute("name")
-            .ok_or_else(|| Error::from("expected `name` attribute in `hostname` node"))?
-            .to_string();
+///A [`Hostname`](struct.Hostname.html) borrowed from the input where
+///possible.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HostnameRef<'a> {
+    pub name: Cow<'a, str>,
+    pub source: HostnameType,
+}
+
+impl<'a> HostnameRef<'a> {
+    fn parse(node: Node<'_, 'a>) -> Result<Self, Error> {
+        let name = borrowed_attribute(node, "name")?;
+        let source = parse_attribute(node, "type")?;
+
+        Ok(HostnameRef { name, source })
+    }
 
-        let s = node
-            .attribute("type")
-            .ok_or_else(|| Error::from("expected `type` attribute in `hostname` node"))?;
-        let source = HostnameType::from_str(s)
-            .map_err(|_| Error::from("expected `source` attribute in `address` node"))?;
+    ///Copies the name into a [`Hostname`](struct.Hostname.html).
+    pub fn to_owned(&self) -> Hostname {
+        self.clone().into_owned()
+    }
 
-        Ok(Hostname { name, source })
+    ///Converts the name into a [`Hostname`](struct.Hostname.html).
+    pub fn into_owned(self) -> Hostname {
+        Hostname {
+            name: self.name.into_owned(),
+            source: self.source,
+        }
     }
 }
 
+///Output of an NSE script.
+///
+///Typed decoders for common scripts are in the
+///[`scripts`](../scripts/index.html) module.
 #[derive(Clone, Debug, PartialEq)]
 pub struct Script {
     pub id: String,
+    ///Human-readable output of the script.
     pub output: String,
+    ///Structured output of the script, from its `elem` and `table` children.
+    pub elements: Vec<ScriptElement>,
+    pub(crate) unrecognized: Unrecognized,
 }
 
 impl Script {
-    fn parse(node: Node) -> Result<Self, Error> {
-        let id = node
-            .attribute("id")
-            .ok_or_else(|| Error::from("expected `id` attribute in `script` node"))?
-            .to_string();
+    ///Returns the value of the top-level element with the given key.
+    pub fn get(&self, key: &str) -> Option<&ScriptValue> {
+        find_script_value(&self.elements, key)
+    }
 
-        let output = node
-            .attribute("output")
-            .ok_or_else(|| Error::from("expected `output` attribute in `script` node"))?
-            .to_string();
+    ///Returns content of the `script` element that is not otherwise modeled.
+    ///Empty unless
+    ///[`ParseOptions::preserve_unknown`](../struct.ParseOptions.html) is set.
+    pub fn unrecognized(&self) -> &Unrecognized {
+        &self.unrecognized
+    }
+}
 
-        Ok(Script { id, output })
+///A [`Script`](struct.Script.html) whose id and output are borrowed from the
+///input where possible. The structured output is not borrowed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScriptRef<'a> {
+    pub id: Cow<'a, str>,
+    ///Human-readable output of the script.
+    pub output: Cow<'a, str>,
+    ///Structured output of the script, from its `elem` and `table` children.
+    pub elements: Vec<ScriptElement>,
+    pub(crate) unrecognized: Unrecognized,
+}
+
+impl<'a> ScriptRef<'a> {
+    pub(crate) fn parse(node: Node<'_, 'a>, ctx: &ParseContext) -> Result<Self, Error> {
+        let id = borrowed_attribute(node, "id")?;
+        let output = borrowed_attribute(node, "output")?;
+        let elements = parse_script_elements(node);
+
+        let mut unrecognized = ctx.unrecognized(node, &["id", "output"]);
+        for child in node.children() {
+            if !matches!(child.tag_name().name(), "elem" | "table") {
+                ctx.preserve(&mut unrecognized, child);
+            }
+        }
+
+        Ok(ScriptRef {
+            id,
+            output,
+            elements,
+            unrecognized,
+        })
+    }
+
+    ///Returns the value of the top-level element with the given key.
+    pub fn get(&self, key: &str) -> Option<&ScriptValue> {
+        find_script_value(&self.elements, key)
+    }
+
+    ///Returns content of the `script` element that is not otherwise modeled.
+    pub fn unrecognized(&self) -> &Unrecognized {
+        &self.unrecognized
+    }
+
+    ///Copies the output into a [`Script`](struct.Script.html).
+    pub fn to_owned(&self) -> Script {
+        self.clone().into_owned()
+    }
+
+    ///Converts the output into a [`Script`](struct.Script.html).
+    pub fn into_owned(self) -> Script {
+        Script {
+            id: self.id.into_owned(),
+            output: self.output.into_owned(),
+            elements: self.elements,
+            unrecognized: self.unrecognized,
+        }
     }
 }
 
+///An `elem` or `table` in the structured output of a script.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScriptElement {
+    pub key: Option<String>,
+    pub value: ScriptValue,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScriptValue {
+    Text(String),
+    Table(Vec<ScriptElement>),
+}
+
+impl ScriptValue {
+    ///Returns the text if this is a `Text` value.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ScriptValue::Text(s) => Some(s),
+            ScriptValue::Table(_) => None,
+        }
+    }
+
+    ///Returns the elements if this is a `Table` value.
+    pub fn as_table(&self) -> Option<&[ScriptElement]> {
+        match self {
+            ScriptValue::Text(_) => None,
+            ScriptValue::Table(t) => Some(t),
+        }
+    }
+
+    ///Returns the value of the element with the given key if this is a
+    ///`Table` value.
+    pub fn get(&self, key: &str) -> Option<&ScriptValue> {
+        self.as_table().and_then(|t| find_script_value(t, key))
+    }
+}
+
+fn find_script_value<'a>(elements: &'a [ScriptElement], key: &str) -> Option<&'a ScriptValue> {
+    elements
+        .iter()
+        .find(|e| e.key.as_deref() == Some(key))
+        .map(|e| &e.value)
+}
+
+fn parse_script_elements(node: Node) -> Vec<ScriptElement> {
+    let mut r = Vec::new();
+
+    for child in node.children() {
+        let value = match child.tag_name().name() {
+            "elem" => ScriptValue::Text(child.text().unwrap_or("").to_string()),
+            "table" => ScriptValue::Table(parse_script_elements(child)),
+            _ => continue,
+        };
+        r.push(ScriptElement {
+            key: child.attribute("key").map(|s| s.to_string()),
+            value,
+        });
+    }
+
+    r
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -246,9 +754,9 @@ mod test {
     <address addr="192.168.59.234" addrtype="ipv4"/>
 </host>
         "#;
-        let doc = Document::parse(&xml).unwrap();
+        let doc = Document::parse(xml).unwrap();
         let ele = doc.root_element();
-        let host = Host::parse(ele).unwrap();
+        let host = Host::parse(ele, &mut ParseContext::default()).unwrap();
 
         assert_eq!(host.scan_start_time, Some(1589292535));
         assert_eq!(host.scan_end_time, Some(1589292535));
@@ -262,9 +770,9 @@ mod test {
     <address addr="192.168.59.234" addrtype="ipv4"/>
 </host>
         "#;
-        let doc = Document::parse(&xml).unwrap();
+        let doc = Document::parse(xml).unwrap();
         let ele = doc.root_element();
-        let host = Host::parse(ele).unwrap();
+        let host = Host::parse(ele, &mut ParseContext::default()).unwrap();
 
         assert!(host.scan_start_time.is_none());
         assert!(host.scan_end_time.is_none());
@@ -278,13 +786,13 @@ mod test {
     <address addr="192.168.59.234" addrtype="ipv4"/>
 </host>
         "#;
-        let doc = Document::parse(&xml).unwrap();
+        let doc = Document::parse(xml).unwrap();
         let ele = doc.root_element();
-        let host_err = Host::parse(ele).unwrap_err();
+        let host_err = Host::parse(ele, &mut ParseContext::default()).unwrap_err();
 
         assert_eq!(
             host_err.to_string(),
-            "error parsing Nmap XML output: failed to parse host start time"
+            "invalid value \"NOT A NUMBER\" for `starttime` attribute at host (line 2, column 7)"
         );
     }
 
@@ -298,13 +806,126 @@ mod test {
 </script></hostscript><times srtt="5263" rttvar="4662" to="100000"/>
 </host>
         "#;
-        let doc = Document::parse(&xml).unwrap();
+        let doc = Document::parse(xml).unwrap();
         let ele = doc.root_element();
-        let script_host = Host::parse(ele).unwrap();
+        let script_host = Host::parse(ele, &mut ParseContext::default()).unwrap();
         let script_output = script_host.scripts().collect::<Vec<_>>()[0];
 
         assert_eq!(script_output.id, "smb-print-text");
         assert_eq!(script_output.output, "false");
+        assert!(script_output.elements.is_empty());
+
+        let time = script_host.scripts().nth(1).unwrap();
+        assert_eq!(
+            time.get("date").and_then(|v| v.as_str()),
+            Some("2021-06-12T03:17:58")
+        );
+        assert_eq!(time.elements.len(), 2);
+    }
+
+    #[test]
+    fn hostname_with_unknown_type() {
+        let xml = r#"
+<host>
+    <status state="up" reason="echo-reply" reason_ttl="53"/>
+    <address addr="45.33.32.156" addrtype="ipv4"/>
+    <hostnames>
+        <hostname name="scanme.nmap.org" type="user"/>
+        <hostname name="scanme.example" type="mdns"/>
+    </hostnames>
+</host>
+        "#;
+        let doc = Document::parse(xml).unwrap();
+        let ele = doc.root_element();
+        let host = Host::parse(ele, &mut ParseContext::default()).unwrap();
+        let names = host.host_names().collect::<Vec<_>>();
+
+        assert_eq!(names[1].source, HostnameType::Other("mdns".to_string()));
+        assert_eq!(names[1].source.to_string(), "mdns");
+    }
+
+    #[test]
+    fn host_name_helpers() {
+        let xml = r#"
+<host>
+    <status state="up" reason="echo-reply" reason_ttl="53"/>
+    <address addr="192.0.2.10" addrtype="ipv4"/>
+    <hostnames>
+        <hostname name="www.example.com" type="PTR"/>
+        <hostname name="intranet.example.com" type="user"/>
+        <hostname name="WWW.example.com." type="PTR"/>
+    </hostnames>
+</host>
+        "#;
+        let doc = Document::parse(xml).unwrap();
+        let ele = doc.root_element();
+        let host = Host::parse(ele, &mut ParseContext::default()).unwrap();
+
+        assert_eq!(host.primary_name(), Some("intranet.example.com"));
+        assert_eq!(
+            host.unique_names(),
+            vec!["www.example.com", "intranet.example.com"]
+        );
+        assert!(host.has_forward_reverse_mismatch());
+    }
+
+    #[test]
+    fn host_key() {
+        let xml = r#"
+<nmaprun start="1">
+<host><status state="up" reason="arp-response" reason_ttl="0"/>
+<address addr="00:00:5e:00:53:01" addrtype="mac"/>
+<address addr="192.0.2.1" addrtype="ipv4"/>
+<address addr="2001:db8::1" addrtype="ipv6"/>
+</host>
+<host><status state="up" reason="arp-response" reason_ttl="0"/>
+<address addr="192.0.2.1" addrtype="ipv4"/>
+<address addr="00:00:5E:00:53:01" addrtype="mac"/>
+</host>
+<host><status state="up" reason="echo-reply" reason_ttl="53"/>
+<address addr="192.0.2.1" addrtype="ipv4"/>
+</host>
+<host><status state="down" reason="no-response" reason_ttl="0"/>
+<address addr="00:00:5E:00:53:02" addrtype="mac"/>
+</host>
+</nmaprun>
+"#;
+        let results = crate::NmapResults::parse(xml).unwrap();
+        let keys = results.hosts().map(Host::key).collect::<Vec<_>>();
+
+        let key = keys[0].clone().unwrap();
+        assert_eq!(key.ip, "192.0.2.1".parse::<IpAddr>().unwrap());
+        assert_eq!(key.mac.as_deref(), Some("00:00:5E:00:53:01"));
+        assert_eq!(key.to_string(), "192.0.2.1 (00:00:5E:00:53:01)");
+        assert_eq!(keys[0], keys[1]);
+        assert_ne!(keys[0], keys[2]);
+        assert_eq!(keys[2].as_ref().unwrap().to_string(), "192.0.2.1");
+        assert_eq!(keys[3], None);
+
+        let unique = keys
+            .iter()
+            .flatten()
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(unique.len(), 2);
+    }
+
+    #[test]
+    fn host_with_only_reverse_name() {
+        let xml = r#"
+<host>
+    <status state="up" reason="echo-reply" reason_ttl="53"/>
+    <address addr="192.0.2.10" addrtype="ipv4"/>
+    <hostnames>
+        <hostname name="www.example.com" type="PTR"/>
+    </hostnames>
+</host>
+        "#;
+        let doc = Document::parse(xml).unwrap();
+        let ele = doc.root_element();
+        let host = Host::parse(ele, &mut ParseContext::default()).unwrap();
+
+        assert_eq!(host.primary_name(), Some("www.example.com"));
+        assert!(!host.has_forward_reverse_mismatch());
     }
 
     #[test]
@@ -315,13 +936,13 @@ mod test {
     <address addr="192.168.59.234" addrtype="ipv4"/>
 </host>
         "#;
-        let doc = Document::parse(&xml).unwrap();
+        let doc = Document::parse(xml).unwrap();
         let ele = doc.root_element();
-        let host_err = Host::parse(ele).unwrap_err();
+        let host_err = Host::parse(ele, &mut ParseContext::default()).unwrap_err();
 
         assert_eq!(
             host_err.to_string(),
-            "error parsing Nmap XML output: failed to parse host end time"
+            "invalid value \"NOT A NUMBER\" for `endtime` attribute at host (line 2, column 30)"
         );
     }
 }
@@ -0,0 +1,118 @@
+//!Reading of possibly compressed input.
+use std::io::{self, Cursor, Read};
+
+///Longest magic number that is checked for.
+const MAGIC_LEN: usize = 6;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Compression {
+    Gzip,
+    Zstd,
+    Xz,
+}
+
+impl Compression {
+    fn detect(magic: &[u8]) -> Option<Self> {
+        if magic.starts_with(&[0x1f, 0x8b]) {
+            Some(Compression::Gzip)
+        } else if magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Compression::Zstd)
+        } else if magic.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Some(Compression::Xz)
+        } else {
+            None
+        }
+    }
+
+    ///Returns the name of the format, which is also the name of the feature
+    ///that enables it.
+    fn name(self) -> &'static str {
+        match self {
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+            Compression::Xz => "xz",
+        }
+    }
+}
+
+///Reads all of `reader` as a string, decompressing it if it starts with
+///the magic number of a compression format whose feature is enabled.
+pub(crate) fn read_to_string<R: Read>(mut reader: R) -> io::Result<String> {
+    let mut magic = [0; MAGIC_LEN];
+    let mut len = 0;
+    while len < MAGIC_LEN {
+        match reader.read(&mut magic[len..]) {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    let mut reader = Cursor::new(&magic[..len]).chain(reader);
+    let mut s = String::new();
+    match Compression::detect(&magic[..len]) {
+        None => reader.read_to_string(&mut s)?,
+        Some(c) => decoder(c, reader)?.read_to_string(&mut s)?,
+    };
+    Ok(s)
+}
+
+fn decoder<'a, R: Read + 'a>(
+    compression: Compression,
+    reader: R,
+) -> io::Result<Box<dyn Read + 'a>> {
+    match compression {
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => Ok(Box::new(flate2::read::MultiGzDecoder::new(reader))),
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => Ok(Box::new(zstd::stream::read::Decoder::new(reader)?)),
+        #[cfg(feature = "xz")]
+        Compression::Xz => Ok(Box::new(xz2::read::XzDecoder::new_multi_decoder(reader))),
+        #[allow(unreachable_patterns)]
+        c => {
+            drop(reader);
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "input is {} compressed, but the `{}` feature is not enabled",
+                    c.name(),
+                    c.name()
+                ),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn uncompressed() {
+        assert_eq!(read_to_string("<a/>".as_bytes()).unwrap(), "<a/>");
+        assert_eq!(read_to_string("".as_bytes()).unwrap(), "");
+        assert_eq!(
+            read_to_string("<nmaprun/>".as_bytes()).unwrap(),
+            "<nmaprun/>"
+        );
+    }
+
+    #[test]
+    fn not_utf8() {
+        let err = read_to_string(&b"<a>\xff</a>"[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[cfg(not(feature = "xz"))]
+    #[test]
+    fn disabled_compression() {
+        let err = read_to_string(&b"\xfd7zXZ\x00\x00"[..]).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            err.to_string(),
+            "input is xz compressed, but the `xz` feature is not enabled"
+        );
+    }
+}
@@ -22,24 +22,172 @@
 //!The API is __not stable__ and is subject to breaking changes until the
 //!crate reaches 1.0. Use with care.
 use roxmltree::{Document, Node};
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
 
+pub mod args;
+pub mod collection;
+pub mod correlate;
+pub mod coverage;
+pub mod cpe;
+pub mod export;
+pub mod history;
 pub mod host;
+mod input;
+pub mod os;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod parse;
 pub mod port;
+pub mod portset;
+pub mod raw;
+pub mod report;
+pub mod rescan;
+pub mod scripts;
+pub mod stats;
+pub mod stream;
+pub mod vulns;
 
-use crate::host::Host;
-use crate::port::Port;
+use crate::args::{ScanOptions, ScanType};
+use crate::coverage::{host_coverage, Coverage};
+use crate::host::{Address, Host, HostRef};
+use crate::parse::{borrowed_optional_attribute, missing_element, parse_attribute, ParseContext};
+use crate::port::{Port, PortProtocol};
+use crate::portset::PortSet;
+use crate::raw::Unrecognized;
+use crate::stats::ScanStats;
+use crate::vulns::Finding;
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     #[error("error parsing file as XML document")]
     XmlError(#[from] roxmltree::Error),
-    #[error("error parsing Nmap XML output: {0}")]
-    InvalidNmapOutput(String),
+    #[error("error reading input")]
+    Io(#[from] std::io::Error),
+    #[error("expected `{expected}` element at {location}")]
+    UnexpectedElement {
+        location: Location,
+        expected: &'static str,
+    },
+    #[error("missing `{element}` element at {location}")]
+    MissingElement {
+        location: Location,
+        element: &'static str,
+    },
+    #[error("missing `{attribute}` attribute at {location}")]
+    MissingAttribute {
+        location: Location,
+        attribute: &'static str,
+    },
+    #[error("invalid value {value:?} for `{attribute}` attribute at {location}")]
+    InvalidAttribute {
+        location: Location,
+        attribute: &'static str,
+        value: String,
+    },
+    #[error("invalid text {value:?} at {location}")]
+    InvalidText { location: Location, value: String },
+    #[error("invalid CPE name: {0}")]
+    InvalidCpe(String),
+    #[error("invalid glob pattern")]
+    InvalidPattern(#[from] glob::PatternError),
 }
 
-impl From<&str> for Error {
-    fn from(s: &str) -> Self {
-        Self::InvalidNmapOutput(s.to_string())
+impl Error {
+    ///Returns where in the document the error occurred, if known.
+    pub fn location(&self) -> Option<&Location> {
+        match self {
+            Error::UnexpectedElement { location, .. }
+            | Error::MissingElement { location, .. }
+            | Error::MissingAttribute { location, .. }
+            | Error::InvalidAttribute { location, .. }
+            | Error::InvalidText { location, .. } => Some(location),
+            Error::XmlError(_) | Error::Io(_) | Error::InvalidCpe(_) | Error::InvalidPattern(_) => {
+                None
+            }
+        }
+    }
+
+    pub(crate) fn location_mut(&mut self) -> Option<&mut Location> {
+        match self {
+            Error::UnexpectedElement { location, .. }
+            | Error::MissingElement { location, .. }
+            | Error::MissingAttribute { location, .. }
+            | Error::InvalidAttribute { location, .. }
+            | Error::InvalidText { location, .. } => Some(location),
+            Error::XmlError(_) | Error::Io(_) | Error::InvalidCpe(_) | Error::InvalidPattern(_) => {
+                None
+            }
+        }
+    }
+}
+
+///A position in Nmap XML output.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Location {
+    ///Path of the element, e.g. `nmaprun/host[312]/ports/port[4]`. Elements
+    ///are only indexed when they have siblings with the same name.
+    pub path: String,
+    ///Line number, starting from 1.
+    pub line: u32,
+    ///Column number, starting from 1.
+    pub column: u32,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} (line {}, column {})",
+            self.path, self.line, self.column
+        )
+    }
+}
+
+///Options controlling how Nmap XML output is parsed.
+#[derive(Clone, Debug)]
+pub struct ParseOptions {
+    ///If true, any malformed record fails the whole parse. If false,
+    ///malformed hosts and ports are skipped, unparseable values are
+    ///defaulted, and each problem is reported as a
+    ///[`ParseWarning`](struct.ParseWarning.html). Defaults to true.
+    pub strict: bool,
+
+    ///If true, elements and attributes the parser does not model are kept
+    ///as [`Unrecognized`](raw/struct.Unrecognized.html) content on the
+    ///struct they were found in. Defaults to false.
+    ///
+    ///Not every element is covered: unknown content of elements such as
+    ///`address`, `hostname`, `times` and `runstats` is still discarded. See
+    ///the [`raw`](raw/index.html) module for the full list.
+    pub preserve_unknown: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            strict: true,
+            preserve_unknown: false,
+        }
+    }
+}
+
+///A problem that was recovered from while parsing in lenient mode.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseWarning {
+    ///Where the problem was found.
+    pub location: Location,
+    ///Description of the problem, including its location.
+    pub reason: String,
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.reason)
     }
 }
 
@@ -49,80 +197,549 @@ pub struct NmapResults {
     ///List of hosts in the Nmap scan.
     hosts: Vec<Host>,
 
+    ///Command line Nmap was run with.
+    pub args: Option<String>,
+
+    ///Scan techniques and the ports they probed.
+    scan_info: Vec<ScanInfo>,
+
     ///Start time of the Nmap scan as seconds since Unix epoch.
     pub scan_start_time: i64,
 
     ///End time of the Nmap scan as seconds since Unix epoch.
     pub scan_end_time: Option<i64>,
+
+    ///Problems recovered from when parsing in lenient mode.
+    warnings: Vec<ParseWarning>,
+
+    ///Content of the `nmaprun` element that is not otherwise modeled.
+    unrecognized: Unrecognized,
 }
 
 impl NmapResults {
     pub fn parse(xml: &str) -> Result<Self, Error> {
+        Self::parse_with_options(xml, &ParseOptions::default())
+    }
+
+    ///Parses Nmap XML output according to `options`.
+    ///
+    ///When `options.strict` is false, problems that were recovered from are
+    ///available from [`warnings()`](#method.warnings).
+    pub fn parse_with_options(xml: &str, options: &ParseOptions) -> Result<Self, Error> {
+        NmapResultsRef::parse_with_options(xml, options).map(NmapResultsRef::into_owned)
+    }
+
+    ///Reads and parses the Nmap XML output in the file at `path`. See
+    ///[`from_reader()`](#method.from_reader).
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::from_reader(File::open(path)?)
+    }
+
+    ///Reads and parses Nmap XML output from `reader`.
+    ///
+    ///Input compressed with gzip, zstd or xz is detected by its magic
+    ///number and decompressed if the `gzip`, `zstd` or `xz` feature is
+    ///enabled. Otherwise, and for input that is not UTF-8, an
+    ///[`Error::Io`](enum.Error.html#variant.Io) is returned.
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, Error> {
+        Self::from_reader_with_options(reader, &ParseOptions::default())
+    }
+
+    ///Reads and parses Nmap XML output from `reader` according to
+    ///`options`. See [`from_reader()`](#method.from_reader).
+    pub fn from_reader_with_options<R: Read>(
+        reader: R,
+        options: &ParseOptions,
+    ) -> Result<Self, Error> {
+        let xml = input::read_to_string(reader)?;
+        Self::parse_with_options(&xml, options)
+    }
+
+    ///Parses Nmap XML output like [`parse()`](#method.parse), but parses
+    ///the hosts in parallel on the global rayon thread pool. Hosts are
+    ///returned in document order.
+    ///
+    ///This is only faster for scans with many hosts.
+    #[cfg(feature = "rayon")]
+    pub fn par_parse(xml: &str) -> Result<Self, Error> {
+        Self::par_parse_with_options(xml, &ParseOptions::default())
+    }
+
+    ///Parses Nmap XML output according to `options`, parsing the hosts in
+    ///parallel. See [`par_parse()`](#method.par_parse).
+    #[cfg(feature = "rayon")]
+    pub fn par_parse_with_options(xml: &str, options: &ParseOptions) -> Result<Self, Error> {
+        parallel::parse(xml, options)
+    }
+
+    ///Returns an iterator over the hosts in the scan.
+    pub fn hosts(&self) -> std::slice::Iter<'_, Host> {
+        self.hosts.iter()
+    }
+
+    ///Sorts the hosts with `compare`. Hosts that compare equal keep their
+    ///order.
+    pub fn sort_hosts_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&Host, &Host) -> Ordering,
+    {
+        self.hosts.sort_by(compare)
+    }
+
+    ///Sorts the hosts numerically by their first IP address, with IPv4
+    ///addresses before IPv6 ones and hosts without an IP address last.
+    pub fn sort_hosts_by_ip(&mut self) {
+        self.hosts.sort_by_key(|h| {
+            let ip = h.addresses().find_map(Address::ip);
+            (ip.is_none(), ip)
+        })
+    }
+
+    ///Sorts the ports of each host by protocol, then by port number, as by
+    ///the [`Ord`](port/struct.Port.html) implementation of `Port`.
+    pub fn sort_ports(&mut self) {
+        for host in &mut self.hosts {
+            host.port_info.ports.sort();
+        }
+    }
+
+    ///Returns an iterator over the problems recovered from while parsing.
+    ///This is always empty for strict parses.
+    pub fn warnings(&self) -> std::slice::Iter<'_, ParseWarning> {
+        self.warnings.iter()
+    }
+
+    ///Returns content of the `nmaprun` element that is not otherwise
+    ///modeled. Empty unless
+    ///[`ParseOptions::preserve_unknown`](struct.ParseOptions.html) is set.
+    pub fn unrecognized(&self) -> &Unrecognized {
+        &self.unrecognized
+    }
+
+    ///Returns an iterator over the scan techniques used and the ports each
+    ///of them probed.
+    pub fn scan_info(&self) -> std::slice::Iter<'_, ScanInfo> {
+        self.scan_info.iter()
+    }
+
+    ///Returns the ports of `protocol` that were probed by any scan
+    ///technique. Ports outside this set were not scanned, whatever their
+    ///state in another scan.
+    pub fn scanned_ports(&self, protocol: &PortProtocol) -> PortSet {
+        scanned_ports(&self.scan_info, protocol)
+    }
+
+    ///Returns which ports of each host were scanned, and in which state,
+    ///for each protocol scanned. Unlike
+    ///[`PortInfo::ports()`](port/struct.PortInfo.html#method.ports), this
+    ///includes the ports that were not listed individually.
+    pub fn coverage(&self) -> std::vec::IntoIter<(&Host, Vec<Coverage>)> {
+        let scan_info = self.scan_info.iter().collect::<Vec<_>>();
+        let mut results = Vec::new();
+        for host in &self.hosts {
+            results.push((host, host_coverage(host, &scan_info)));
+        }
+
+        results.into_iter()
+    }
+
+    ///Returns the command line Nmap was run with, split into options and
+    ///targets.
+    pub fn scan_options(&self) -> Option<ScanOptions> {
+        self.args.as_deref().map(ScanOptions::parse)
+    }
+
+    ///Returns an iterator over the ports in the scan.
+    pub fn iter_ports(&self) -> std::vec::IntoIter<(&Host, &Port)> {
+        let mut results = Vec::new();
+        for host in &self.hosts {
+            for port in &host.port_info.ports {
+                results.push((host, port));
+            }
+        }
+
+        results.into_iter()
+    }
+
+    ///Returns an iterator over the vulnerability findings reported by
+    ///scripts on all hosts.
+    pub fn findings(&self) -> std::vec::IntoIter<Finding<'_>> {
+        let mut results = Vec::new();
+        for host in &self.hosts {
+            results.extend(Finding::from_host(host));
+        }
+
+        results.into_iter()
+    }
+
+    ///Returns summary statistics for the scan.
+    pub fn stats(&self) -> ScanStats<'_> {
+        ScanStats::new(self)
+    }
+}
+
+///Scan results whose strings are borrowed from the XML they were parsed
+///from where possible.
+///
+///This avoids copying every attribute value, which dominates parse time on
+///large scans. Strings that contained entity references, such as most
+///script output, are still copied. Use
+///[`to_owned()`](#method.to_owned) to convert to
+///[`NmapResults`](struct.NmapResults.html).
+///
+///```
+///# use std::path::PathBuf;
+///# use std::fs;
+///use nmap_xml_parser::NmapResultsRef;
+///# let mut nmap_xml_file = PathBuf::new();
+///# nmap_xml_file.push(&std::env::var("CARGO_MANIFEST_DIR").unwrap());
+///# nmap_xml_file.push("tests/scanme-version.xml");
+///let content = fs::read_to_string(nmap_xml_file).unwrap();
+///let results = NmapResultsRef::parse(&content).unwrap();
+///
+///for host in results.hosts() {
+///    for port in host.port_info.ports() {
+///        println!("{} {}", port.port_number, port.status.reason);
+///    }
+///}
+///let results = results.to_owned();
+///```
+#[derive(Clone, Debug)]
+pub struct NmapResultsRef<'a> {
+    hosts: Vec<HostRef<'a>>,
+
+    ///Command line Nmap was run with.
+    pub args: Option<Cow<'a, str>>,
+
+    scan_info: Vec<ScanInfo>,
+
+    ///Start time of the Nmap scan as seconds since Unix epoch.
+    pub scan_start_time: i64,
+
+    ///End time of the Nmap scan as seconds since Unix epoch.
+    pub scan_end_time: Option<i64>,
+
+    warnings: Vec<ParseWarning>,
+
+    unrecognized: Unrecognized,
+}
+
+impl<'a> NmapResultsRef<'a> {
+    pub fn parse(xml: &'a str) -> Result<Self, Error> {
+        Self::parse_with_options(xml, &ParseOptions::default())
+    }
+
+    ///Parses Nmap XML output according to `options`.
+    pub fn parse_with_options(xml: &'a str, options: &ParseOptions) -> Result<Self, Error> {
+        let mut ctx = ParseContext::new(options);
         let doc = Document::parse(xml)?;
         let root_element = doc.root_element();
         if root_element.tag_name().name() != "nmaprun" {
-            return Err(Error::from("expected `nmaprun` root tag"));
+            return Err(Error::UnexpectedElement {
+                location: Location::of(root_element),
+                expected: "nmaprun",
+            });
         }
 
-        let scan_start_time = root_element
-            .attribute("start")
-            .ok_or_else(|| Error::from("expected start time attribute"))
-            .and_then(|s| {
-                s.parse::<i64>()
-                    .map_err(|_| Error::from("failed to parse start time"))
-            })?;
+        let args = borrowed_optional_attribute(root_element, "args");
+        let scan_start_time = parse_attribute(root_element, "start")?;
+        let mut unrecognized = ctx.unrecognized(root_element, &["args", "start"]);
 
-        let mut hosts: Vec<Host> = Vec::new();
+        let mut hosts = Vec::new();
+        let mut scan_info = Vec::new();
         let mut scan_end_time = None;
 
         for child in root_element.children() {
             match child.tag_name().name() {
                 "host" => {
-                    hosts.push(Host::parse(child)?);
+                    let host = HostRef::parse(child, &mut ctx);
+                    hosts.extend(ctx.or_skip(child, host)?);
+                }
+                "scaninfo" => {
+                    let info = ScanInfo::parse(child);
+                    scan_info.extend(ctx.or_skip(child, info)?);
+                }
+                "runstats" => {
+                    let end_time = parse_runstats(child);
+                    scan_end_time = ctx.or_skip(child, end_time)?;
                 }
-                "runstats" => scan_end_time = Some(parse_runstats(child)?),
-                _ => {}
+                _ => ctx.preserve(&mut unrecognized, child),
             }
         }
 
-        Ok(NmapResults {
+        Ok(NmapResultsRef {
             hosts,
+            args,
+            scan_info,
             scan_start_time,
             scan_end_time,
+            warnings: ctx.into_warnings(),
+            unrecognized,
         })
     }
 
     ///Returns an iterator over the hosts in the scan.
-    pub fn hosts(&self) -> std::slice::Iter<Host> {
+    pub fn hosts(&self) -> std::slice::Iter<'_, HostRef<'a>> {
         self.hosts.iter()
     }
 
-    ///Returns an iterator over the ports in the scan.
-    pub fn iter_ports(&self) -> std::vec::IntoIter<(&Host, &Port)> {
-        let mut results = Vec::new();
-        for host in &self.hosts {
-            for port in &host.port_info.ports {
-                results.push((host, port));
-            }
+    ///Returns an iterator over the problems recovered from while parsing.
+    ///This is always empty for strict parses.
+    pub fn warnings(&self) -> std::slice::Iter<'_, ParseWarning> {
+        self.warnings.iter()
+    }
+
+    ///Returns an iterator over the scan techniques used and the ports each
+    ///of them probed.
+    pub fn scan_info(&self) -> std::slice::Iter<'_, ScanInfo> {
+        self.scan_info.iter()
+    }
+
+    ///Returns the ports of `protocol` that were probed by any scan
+    ///technique.
+    pub fn scanned_ports(&self, protocol: &PortProtocol) -> PortSet {
+        scanned_ports(&self.scan_info, protocol)
+    }
+
+    ///Returns content of the `nmaprun` element that is not otherwise
+    ///modeled.
+    pub fn unrecognized(&self) -> &Unrecognized {
+        &self.unrecognized
+    }
+
+    ///Copies the results into an [`NmapResults`](struct.NmapResults.html).
+    pub fn to_owned(&self) -> NmapResults {
+        self.clone().into_owned()
+    }
+
+    ///Converts the results into an [`NmapResults`](struct.NmapResults.html),
+    ///copying only the strings that are still borrowed.
+    pub fn into_owned(self) -> NmapResults {
+        NmapResults {
+            hosts: self.hosts.into_iter().map(HostRef::into_owned).collect(),
+            args: self.args.map(Cow::into_owned),
+            scan_info: self.scan_info,
+            scan_start_time: self.scan_start_time,
+            scan_end_time: self.scan_end_time,
+            warnings: self.warnings,
+            unrecognized: self.unrecognized,
         }
+    }
+}
 
-        results.into_iter()
+///A scan technique and the ports it probed, from a `scaninfo` element.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScanInfo {
+    pub scan_type: ScanType,
+    pub protocol: PortProtocol,
+    ///The ports that were probed.
+    pub services: PortSet,
+}
+
+impl ScanInfo {
+    fn parse(node: Node) -> Result<Self, Error> {
+        Ok(ScanInfo {
+            scan_type: parse_attribute(node, "type")?,
+            protocol: parse_attribute(node, "protocol")?,
+            services: parse_attribute(node, "services")?,
+        })
     }
 }
 
+fn scanned_ports(scan_info: &[ScanInfo], protocol: &PortProtocol) -> PortSet {
+    scan_info
+        .iter()
+        .filter(|i| i.protocol == *protocol)
+        .fold(PortSet::new(), |r, i| r.union(&i.services))
+}
+
 fn parse_runstats(node: Node) -> Result<i64, Error> {
     for child in node.children() {
         if child.tag_name().name() == "finished" {
-            let finished = child
-                .attribute("time")
-                .ok_or_else(|| Error::from("expected `time` `runstats`.`finished`"))
-                .and_then(|s| {
-                    s.parse::<i64>()
-                        .map_err(|_| Error::from("failed to parse end time"))
-                })?;
-            return Ok(finished);
+            return parse_attribute(child, "time");
         }
     }
 
-    Err(Error::from("expected `finished` tag in `runstats`"))
+    Err(missing_element(node, "finished"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MALFORMED: &str = r#"
+<nmaprun start="1588318812">
+<host><status state="sleeping" reason="echo-reply" reason_ttl="300"/>
+<address addr="192.0.2.1" addrtype="ipv4"/>
+<ports>
+<port protocol="tcp" portid="22"><state state="open" reason="syn-ack" reason_ttl="53"/><service name="ssh" method="guessed" conf="3"/></port>
+<port protocol="tcp" portid="80"><state reason="syn-ack" reason_ttl="53"/></port>
+</ports>
+</host>
+<host><address addr="192.0.2.2" addrtype="ipv4"/></host>
+<host><status state="up" reason="echo-reply" reason_ttl="53"/>
+<address addr="192.0.2.3" addrtype="ipv4"/>
+</host>
+</nmaprun>
+"#;
+
+    #[test]
+    fn strict_parse_fails_on_malformed_record() {
+        let err = NmapResults::parse(MALFORMED).unwrap_err();
+
+        match &err {
+            Error::InvalidAttribute {
+                location,
+                attribute,
+                value,
+            } => {
+                assert_eq!(location.path, "nmaprun/host[1]/status");
+                assert_eq!(location.line, 3);
+                assert_eq!(*attribute, "state");
+                assert_eq!(value, "sleeping");
+            }
+            e => panic!("unexpected error: {:?}", e),
+        }
+        assert_eq!(
+            err.to_string(),
+            "invalid value \"sleeping\" for `state` attribute at nmaprun/host[1]/status (line 3, column 15)"
+        );
+    }
+
+    #[test]
+    fn unexpected_root_element() {
+        let err = NmapResults::parse("<nmap/>").unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "expected `nmaprun` element at nmap (line 1, column 1)"
+        );
+    }
+
+    #[test]
+    fn lenient_parse_collects_warnings() {
+        let options = ParseOptions {
+            strict: false,
+            ..Default::default()
+        };
+        let results = NmapResults::parse_with_options(MALFORMED, &options).unwrap();
+
+        let hosts = results.hosts().collect::<Vec<_>>();
+        assert_eq!(hosts.len(), 2);
+        assert_eq!(
+            hosts[0].status.state,
+            host::HostState::Other("sleeping".to_string())
+        );
+        assert_eq!(hosts[0].status.reason_ttl, 0);
+
+        let ports = hosts[0].port_info.ports().collect::<Vec<_>>();
+        assert_eq!(ports.len(), 1);
+        assert_eq!(ports[0].port_number, 22);
+        assert!(ports[0].service_info.is_none());
+
+        let paths = results
+            .warnings()
+            .map(|w| w.location.path.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec![
+                "nmaprun/host[1]/status",
+                "nmaprun/host[1]/status",
+                "nmaprun/host[1]/ports/port[1]/service",
+                "nmaprun/host[1]/ports/port[2]/state",
+                "nmaprun/host[2]",
+            ]
+        );
+        assert_eq!(
+            results.warnings().nth(3).unwrap().to_string(),
+            "missing `state` attribute at nmaprun/host[1]/ports/port[2]/state (line 7, column 34)"
+        );
+        assert_eq!(
+            results.warnings().nth(4).unwrap().to_string(),
+            "missing `status` element at nmaprun/host[2] (line 10, column 1)"
+        );
+    }
+
+    #[test]
+    fn borrowed_parse() {
+        let xml = r#"
+<nmaprun start="1588318812">
+<host><status state="up" reason="echo-reply" reason_ttl="53"/>
+<address addr="192.0.2.1" addrtype="ipv4"/>
+<hostnames><hostname name="a.example" type="PTR"/></hostnames>
+<ports>
+<port protocol="tcp" portid="80"><state state="open" reason="syn-ack" reason_ttl="53"/><service name="http" product="nginx" method="probed" conf="10"/><script id="http-title" output="A &amp; B"/></port>
+</ports>
+</host>
+</nmaprun>
+"#;
+        let results = NmapResultsRef::parse(xml).unwrap();
+        let host = results.hosts().next().unwrap();
+        let port = host.port_info.ports().next().unwrap();
+
+        assert!(matches!(
+            host.host_names().next().unwrap().name,
+            Cow::Borrowed("a.example")
+        ));
+        assert!(matches!(port.status.reason, Cow::Borrowed("syn-ack")));
+        let service = port.service_info.as_ref().unwrap();
+        assert!(matches!(service.product, Some(Cow::Borrowed("nginx"))));
+        assert!(matches!(port.scripts[0].id, Cow::Borrowed("http-title")));
+        assert!(matches!(&port.scripts[0].output, Cow::Owned(s) if s == "A & B"));
+
+        assert_eq!(
+            format!("{:?}", results.to_owned()),
+            format!("{:?}", NmapResults::parse(xml).unwrap())
+        );
+    }
+
+    #[test]
+    fn sorting() {
+        let host = |addr: &str, ports: &[(&str, u16)]| {
+            let addrtype = if addr.contains(':') { "ipv6" } else { "ipv4" };
+            let ports = ports
+                .iter()
+                .map(|(protocol, number)| {
+                    format!(
+                        r#"<port protocol="{}" portid="{}"><state state="open" reason="syn-ack" reason_ttl="64"/></port>"#,
+                        protocol, number
+                    )
+                })
+                .collect::<String>();
+            format!(
+                r#"<host><status state="up" reason="user-set" reason_ttl="0"/><address addr="{}" addrtype="{}"/><ports>{}</ports></host>"#,
+                addr, addrtype, ports
+            )
+        };
+        let xml = format!(
+            r#"<nmaprun start="1">{}{}{}{}</nmaprun>"#,
+            host("2001:db8::1", &[]),
+            host("192.0.2.10", &[("udp", 53), ("tcp", 443), ("tcp", 22)]),
+            host("192.0.2.9", &[]),
+            host("10.0.0.1", &[]),
+        );
+        let mut results = NmapResults::parse(&xml).unwrap();
+
+        results.sort_hosts_by_ip();
+        results.sort_ports();
+        let addresses = results
+            .hosts()
+            .map(|h| h.display_address().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            addresses,
+            vec!["10.0.0.1", "192.0.2.9", "192.0.2.10", "2001:db8::1"]
+        );
+        let ports = results.hosts[2]
+            .port_info
+            .ports()
+            .map(|p| format!("{}/{}", p.port_number, p.protocol))
+            .collect::<Vec<_>>();
+        assert_eq!(ports, vec!["22/tcp", "443/tcp", "53/udp"]);
+
+        results.sort_hosts_by(|a, b| b.port_info.ports().len().cmp(&a.port_info.ports().len()));
+        assert_eq!(results.hosts[0].display_address().unwrap(), "192.0.2.10");
+        assert_eq!(results.hosts[1].display_address().unwrap(), "10.0.0.1");
+    }
 }
@@ -0,0 +1,112 @@
+//!OS detection related structs.
+use roxmltree::Node;
+
+use crate::cpe::{parse_cpe_nodes, Cpe};
+use crate::parse::{attribute, parse_attribute};
+use crate::raw::Unrecognized;
+use crate::{Error, ParseContext};
+
+///A candidate operating system from Nmap's OS detection.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OsMatch {
+    pub name: String,
+    pub accuracy: u8,
+    pub classes: Vec<OsClass>,
+    pub(crate) unrecognized: Unrecognized,
+}
+
+impl OsMatch {
+    fn parse(node: Node, ctx: &ParseContext) -> Result<Self, Error> {
+        let name = attribute(node, "name")?.to_string();
+        let accuracy = parse_attribute(node, "accuracy")?;
+        let mut unrecognized = ctx.unrecognized(node, &["name", "accuracy"]);
+
+        let mut classes = Vec::new();
+        for child in node.children() {
+            if child.tag_name().name() == "osclass" {
+                classes.push(OsClass::parse(child, ctx)?);
+            } else {
+                ctx.preserve(&mut unrecognized, child);
+            }
+        }
+
+        Ok(OsMatch {
+            name,
+            accuracy,
+            classes,
+            unrecognized,
+        })
+    }
+
+    ///Returns content of the `osmatch` element that is not otherwise modeled.
+    ///Empty unless
+    ///[`ParseOptions::preserve_unknown`](../struct.ParseOptions.html) is set.
+    pub fn unrecognized(&self) -> &Unrecognized {
+        &self.unrecognized
+    }
+}
+
+///A classification of an [`OsMatch`](struct.OsMatch.html).
+#[derive(Clone, Debug, PartialEq)]
+pub struct OsClass {
+    pub device_type: Option<String>,
+    pub vendor: String,
+    pub family: String,
+    pub generation: Option<String>,
+    pub accuracy: u8,
+    pub cpe: Vec<Cpe>,
+    ///`cpe` elements whose text is not a valid CPE name, verbatim.
+    pub unparsed_cpe: Vec<String>,
+    pub(crate) unrecognized: Unrecognized,
+}
+
+impl OsClass {
+    fn parse(node: Node, ctx: &ParseContext) -> Result<Self, Error> {
+        let device_type = node.attribute("type").map(|s| s.to_string());
+
+        let vendor = attribute(node, "vendor")?.to_string();
+        let family = attribute(node, "osfamily")?.to_string();
+        let generation = node.attribute("osgen").map(|s| s.to_string());
+        let accuracy = parse_attribute(node, "accuracy")?;
+        let (cpe, unparsed_cpe) = parse_cpe_nodes(node);
+
+        let mut unrecognized =
+            ctx.unrecognized(node, &["type", "vendor", "osfamily", "osgen", "accuracy"]);
+        for child in node.children() {
+            if child.tag_name().name() != "cpe" {
+                ctx.preserve(&mut unrecognized, child);
+            }
+        }
+
+        Ok(OsClass {
+            device_type,
+            vendor,
+            family,
+            generation,
+            accuracy,
+            cpe,
+            unparsed_cpe,
+            unrecognized,
+        })
+    }
+
+    ///Returns content of the `osclass` element that is not otherwise modeled.
+    ///Empty unless
+    ///[`ParseOptions::preserve_unknown`](../struct.ParseOptions.html) is set.
+    pub fn unrecognized(&self) -> &Unrecognized {
+        &self.unrecognized
+    }
+}
+
+pub(crate) fn parse_os_node(node: Node, ctx: &mut ParseContext) -> Result<Vec<OsMatch>, Error> {
+    let mut r = Vec::new();
+
+    for child in node.children() {
+        if child.tag_name().name() == "osmatch" {
+            let os_match = OsMatch::parse(child, ctx);
+            r.extend(ctx.or_skip(child, os_match)?);
+        }
+    }
+
+    Ok(r)
+}
@@ -0,0 +1,167 @@
+//!Parsing of hosts in parallel, with the `rayon` feature.
+use std::borrow::Cow;
+
+use rayon::prelude::*;
+
+use crate::parse::ParseContext;
+use crate::stream::{parse_chunk, Chunk, HostSplitter};
+use crate::{Error, Location, NmapResults, NmapResultsRef, ParseOptions};
+
+const SPLIT_SIZE: usize = 64 * 1024;
+
+///Parses `xml` like
+///[`NmapResults::parse_with_options()`](../struct.NmapResults.html#method.parse_with_options),
+///but with each `host` element parsed on its own in parallel.
+pub(crate) fn parse(xml: &str, options: &ParseOptions) -> Result<NmapResults, Error> {
+    let mut splitter = HostSplitter::new();
+    let mut chunks = Vec::new();
+    for block in xml.as_bytes().chunks(SPLIT_SIZE) {
+        splitter.push(block);
+        while let Some(chunk) = splitter.next_chunk() {
+            chunks.push(chunk?);
+        }
+    }
+    chunks.extend(splitter.remainder());
+
+    let parsed = chunks
+        .par_iter()
+        .map(|chunk| {
+            let mut ctx = ParseContext::new(options);
+            let host = parse_chunk(chunk, &mut ctx);
+            (host, ctx.into_warnings())
+        })
+        .collect::<Vec<_>>();
+
+    let skeleton = skeleton(xml, &chunks);
+    let rest = NmapResultsRef::parse_with_options(&skeleton, options);
+
+    let mut hosts = Vec::with_capacity(parsed.len());
+    let mut warnings = Vec::new();
+    let mut root_warnings = match &rest {
+        Ok(rest) => rest.warnings.clone(),
+        Err(_) => Vec::new(),
+    }
+    .into_iter()
+    .peekable();
+
+    for (chunk, (host, host_warnings)) in chunks.iter().zip(parsed) {
+        //Errors outside of hosts are returned in document order too.
+        if let Err(e) = &rest {
+            if !e.location().is_some_and(|l| before(&chunk.origin, l)) {
+                break;
+            }
+        }
+        while let Some(w) = root_warnings.next_if(|w| before(&w.location, &chunk.origin)) {
+            warnings.push(w);
+        }
+
+        hosts.extend(host?);
+        warnings.extend(host_warnings);
+    }
+    warnings.extend(root_warnings);
+
+    let rest = rest?;
+    Ok(NmapResults {
+        hosts,
+        args: rest.args.map(Cow::into_owned),
+        scan_info: rest.scan_info,
+        scan_start_time: rest.scan_start_time,
+        scan_end_time: rest.scan_end_time,
+        warnings,
+        unrecognized: rest.unrecognized,
+    })
+}
+
+///Returns `xml` with each of `chunks` left out. The newlines in them, and
+///enough spaces to fill their last line, are kept so that locations in the
+///rest of the document do not change.
+fn skeleton(xml: &str, chunks: &[Chunk]) -> String {
+    let mut r = String::new();
+    let mut end = 0;
+
+    for chunk in chunks {
+        r.push_str(&xml[end..chunk.offset]);
+        let (lines, last) = match chunk.xml.rfind('\n') {
+            Some(i) => (chunk.xml.matches('\n').count(), &chunk.xml[i + 1..]),
+            None => (0, chunk.xml.as_str()),
+        };
+        r.push_str(&"\n".repeat(lines));
+        r.push_str(&" ".repeat(last.chars().count()));
+        end = chunk.offset + chunk.xml.len();
+    }
+    r.push_str(&xml[end..]);
+
+    r
+}
+
+fn before(a: &Location, b: &Location) -> bool {
+    (a.line, a.column) < (b.line, b.column)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MALFORMED: &str = r#"
+<nmaprun start="1588318812">
+<host><status state="sleeping" reason="echo-reply" reason_ttl="300"/>
+<address addr="192.0.2.1" addrtype="ipv4"/>
+<ports>
+<port protocol="tcp" portid="80"><state reason="syn-ack" reason_ttl="53"/></port>
+</ports>
+</host>
+<verbose level="föö"/><host><address addr="192.0.2.2" addrtype="ipv4"/></host>
+<host><status state="up" reason="echo-reply" reason_ttl="53"/>
+<address addr="192.0.2.3" addrtype="ipv4"/>
+</host>
+<runstats><finished time="soon"/></runstats>
+</nmaprun>
+"#;
+
+    fn same_as_parse(xml: &str, options: &ParseOptions) {
+        let expected = NmapResults::parse_with_options(xml, options);
+        let actual = parse(xml, options);
+        assert_eq!(format!("{:?}", actual), format!("{:?}", expected));
+    }
+
+    #[test]
+    fn strict() {
+        same_as_parse(MALFORMED, &ParseOptions::default());
+
+        let xml = MALFORMED
+            .replace("sleeping", "up")
+            .replace("<state reason", "<state state=\"open\" reason");
+        same_as_parse(&xml, &ParseOptions::default());
+    }
+
+    #[test]
+    fn lenient() {
+        let options = ParseOptions {
+            strict: false,
+            preserve_unknown: true,
+        };
+        same_as_parse(MALFORMED, &options);
+
+        let results = parse(MALFORMED, &options).unwrap();
+        assert_eq!(results.hosts().count(), 2);
+        assert_eq!(results.warnings().count(), 5);
+    }
+
+    #[test]
+    fn single_host() {
+        let end = MALFORMED.find("<verbose").unwrap();
+        let xml = format!("{}</nmaprun>", &MALFORMED[..end]);
+        same_as_parse(&xml, &ParseOptions::default());
+
+        let options = ParseOptions {
+            strict: false,
+            ..Default::default()
+        };
+        same_as_parse(&xml, &options);
+    }
+
+    #[test]
+    fn unexpected_root_element() {
+        same_as_parse("<nmap><host/></nmap>", &ParseOptions::default());
+    }
+}
@@ -0,0 +1,234 @@
+//!Helpers shared by the parse functions.
+use roxmltree::Node;
+use std::borrow::Cow;
+use std::str::FromStr;
+
+use crate::raw::{RawAttribute, RawElement, Unrecognized};
+use crate::{Error, Location, ParseOptions, ParseWarning};
+
+///State threaded through the parse functions.
+#[derive(Debug, Default)]
+pub(crate) struct ParseContext {
+    options: ParseOptions,
+    warnings: Vec<ParseWarning>,
+}
+
+impl ParseContext {
+    pub(crate) fn new(options: &ParseOptions) -> Self {
+        ParseContext {
+            options: options.clone(),
+            warnings: Vec::new(),
+        }
+    }
+
+    pub(crate) fn into_warnings(self) -> Vec<ParseWarning> {
+        self.warnings
+    }
+
+    pub(crate) fn warnings(&self) -> &[ParseWarning] {
+        &self.warnings
+    }
+
+    ///Moves the warnings recorded since the first `from` to `origin`. See
+    ///[`Location::offset()`](../struct.Location.html#method.offset).
+    pub(crate) fn offset_warnings(&mut self, from: usize, origin: &Location) {
+        for w in &mut self.warnings[from..] {
+            let old = w.location.to_string();
+            w.location.offset(origin);
+            w.reason = w.reason.replace(&old, &w.location.to_string());
+        }
+    }
+
+    ///Returns `r` in strict mode. In lenient mode an error is recorded as a
+    ///warning and `default()` is returned instead. `node` is used as the
+    ///location of errors that do not carry one.
+    pub(crate) fn or_default<T, F>(
+        &mut self,
+        node: Node,
+        r: Result<T, Error>,
+        default: F,
+    ) -> Result<T, Error>
+    where
+        F: FnOnce() -> T,
+    {
+        match r {
+            Err(e) if !self.options.strict => {
+                self.warn(node, e);
+                Ok(default())
+            }
+            r => r,
+        }
+    }
+
+    ///Like [`or_default()`](#method.or_default), but drops the value in
+    ///lenient mode.
+    pub(crate) fn or_skip<T>(
+        &mut self,
+        node: Node,
+        r: Result<T, Error>,
+    ) -> Result<Option<T>, Error> {
+        self.or_default(node, r.map(Some), || None)
+    }
+
+    ///Returns the attributes of `node` not listed in `known`, if unknown
+    ///content is being preserved.
+    pub(crate) fn unrecognized(&self, node: Node, known: &[&str]) -> Unrecognized {
+        let mut r = Unrecognized::default();
+        if self.options.preserve_unknown {
+            r.attributes = node
+                .attributes()
+                .iter()
+                .filter(|a| !known.contains(&a.name()))
+                .map(|a| RawAttribute {
+                    name: a.name().to_string(),
+                    value: a.value().to_string(),
+                })
+                .collect();
+        }
+        r
+    }
+
+    ///Adds `node` to `unrecognized` if it is an element and unknown content
+    ///is being preserved.
+    pub(crate) fn preserve(&self, unrecognized: &mut Unrecognized, node: Node) {
+        if self.options.preserve_unknown && node.is_element() {
+            unrecognized.elements.push(RawElement::from_node(node));
+        }
+    }
+
+    fn warn(&mut self, node: Node, e: Error) {
+        let location = e.location().cloned().unwrap_or_else(|| Location::of(node));
+        let reason = match e.location() {
+            Some(_) => e.to_string(),
+            None => format!("{} at {}", e, location),
+        };
+        self.warnings.push(ParseWarning { location, reason });
+    }
+}
+
+impl Location {
+    ///Returns the location of the start of `node`.
+    pub(crate) fn of(node: Node) -> Self {
+        Location::at(node, node.range().start)
+    }
+
+    ///Returns the location of the `name` attribute of `node`, or of `node`
+    ///itself if it has no such attribute.
+    pub(crate) fn of_attribute(node: Node, name: &str) -> Self {
+        match node.attribute_node(name) {
+            Some(a) => Location::at(node, a.range().start),
+            None => Location::of(node),
+        }
+    }
+
+    ///Converts a location in a `host` element that was parsed on its own to
+    ///a location in the whole document, where the element starts at
+    ///`origin`.
+    pub(crate) fn offset(&mut self, origin: &Location) {
+        if self.line == 1 {
+            self.column += origin.column - 1;
+        }
+        self.line += origin.line - 1;
+        self.path = match self.path.strip_prefix("host") {
+            Some(rest) => format!("{}{}", origin.path, rest),
+            None => self.path.clone(),
+        };
+    }
+
+    fn at(node: Node, pos: usize) -> Self {
+        let text_pos = node.document().text_pos_at(pos);
+        Location {
+            path: element_path(node),
+            line: text_pos.row,
+            column: text_pos.col,
+        }
+    }
+}
+
+///Returns an XPath-like path to `node`, e.g. `nmaprun/host[312]/ports`.
+///Elements are only indexed when they have siblings of the same name.
+pub(crate) fn element_path(node: Node) -> String {
+    let mut components = Vec::new();
+
+    for n in node.ancestors().filter(|n| n.is_element()) {
+        let name = n.tag_name().name();
+        let same_name = |s: &Node| s.is_element() && s.tag_name().name() == name;
+
+        let before = n.prev_siblings().skip(1).filter(same_name).count();
+        let after = n.next_siblings().skip(1).filter(same_name).count();
+        if before + after > 0 {
+            components.push(format!("{}[{}]", name, before + 1));
+        } else {
+            components.push(name.to_string());
+        }
+    }
+
+    components.reverse();
+    components.join("/")
+}
+
+///Returns an error for a missing `element` child of `node`.
+pub(crate) fn missing_element(node: Node, element: &'static str) -> Error {
+    Error::MissingElement {
+        location: Location::of(node),
+        element,
+    }
+}
+
+///Returns the value of the `name` attribute of `node`.
+pub(crate) fn attribute<'a>(node: Node<'a, '_>, name: &'static str) -> Result<&'a str, Error> {
+    node.attribute(name).ok_or_else(|| Error::MissingAttribute {
+        location: Location::of(node),
+        attribute: name,
+    })
+}
+
+///Returns the value of the `name` attribute of `node`, borrowed from the
+///input unless it had to be unescaped or normalized.
+pub(crate) fn borrowed_attribute<'input>(
+    node: Node<'_, 'input>,
+    name: &'static str,
+) -> Result<Cow<'input, str>, Error> {
+    borrowed_optional_attribute(node, name).ok_or_else(|| Error::MissingAttribute {
+        location: Location::of(node),
+        attribute: name,
+    })
+}
+
+///Like [`borrowed_attribute()`](fn.borrowed_attribute.html), but returns
+///`None` if the attribute is missing.
+pub(crate) fn borrowed_optional_attribute<'input>(
+    node: Node<'_, 'input>,
+    name: &str,
+) -> Option<Cow<'input, str>> {
+    let a = node.attribute_node(name)?;
+    let input = node.document().input_text();
+
+    match input.get(a.value_range()) {
+        Some(raw) if raw == a.value() => Some(Cow::Borrowed(raw)),
+        _ => Some(Cow::Owned(a.value().to_string())),
+    }
+}
+
+///Parses the value of the `name` attribute of `node`.
+pub(crate) fn parse_attribute<T: FromStr>(node: Node, name: &'static str) -> Result<T, Error> {
+    attribute(node, name).and_then(|s| parse_value(node, name, s))
+}
+
+///Parses the value of the `name` attribute of `node`, if present.
+pub(crate) fn parse_optional_attribute<T: FromStr>(
+    node: Node,
+    name: &'static str,
+) -> Result<Option<T>, Error> {
+    node.attribute(name)
+        .map(|s| parse_value(node, name, s))
+        .transpose()
+}
+
+fn parse_value<T: FromStr>(node: Node, name: &'static str, s: &str) -> Result<T, Error> {
+    s.parse().map_err(|_| Error::InvalidAttribute {
+        location: Location::of_attribute(node, name),
+        attribute: name,
+        value: s.to_string(),
+    })
+}
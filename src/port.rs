@@ -1,92 +1,309 @@
 //!Port related structs and enums.
 use roxmltree::Node;
-use std::str::FromStr;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
 use strum_macros::{Display, EnumString};
 
-use crate::Error;
+use crate::cpe::{parse_cpe_nodes, Cpe};
+use crate::host::{Script, ScriptRef};
+use crate::parse::{
+    attribute, borrowed_attribute, borrowed_optional_attribute, missing_element, parse_attribute,
+    parse_optional_attribute,
+};
+use crate::portset::PortSet;
+use crate::raw::Unrecognized;
+use crate::{Error, ParseContext};
 
 #[derive(Clone, Debug, Default)]
 pub struct PortInfo {
     pub(crate) ports: Vec<Port>,
+    pub(crate) extra_ports: Vec<ExtraPorts>,
+    pub(crate) unrecognized: Unrecognized,
 }
 
 impl PortInfo {
-    pub(crate) fn parse(node: Node) -> Result<Self, Error> {
+    ///Returns an iterator over the ports associated with this host.
+    pub fn ports(&self) -> std::slice::Iter<'_, Port> {
+        self.ports.iter()
+    }
+
+    ///Returns an iterator over the groups of ports that Nmap did not list
+    ///individually because they share a state.
+    pub fn extra_ports(&self) -> std::slice::Iter<'_, ExtraPorts> {
+        self.extra_ports.iter()
+    }
+
+    ///Returns content of the `ports` element that is not otherwise modeled.
+    pub fn unrecognized(&self) -> &Unrecognized {
+        &self.unrecognized
+    }
+}
+
+///A [`PortInfo`](struct.PortInfo.html) whose ports are borrowed from the
+///input where possible.
+#[derive(Clone, Debug, Default)]
+pub struct PortInfoRef<'a> {
+    pub(crate) ports: Vec<PortRef<'a>>,
+    pub(crate) extra_ports: Vec<ExtraPorts>,
+    pub(crate) unrecognized: Unrecognized,
+}
+
+impl<'a> PortInfoRef<'a> {
+    pub(crate) fn parse(node: Node<'_, 'a>, ctx: &mut ParseContext) -> Result<Self, Error> {
         let mut ports = Vec::new();
+        let mut extra_ports = Vec::new();
+        let mut unrecognized = ctx.unrecognized(node, &[]);
 
         for child in node.children() {
-            #[allow(clippy::single_match)]
             match child.tag_name().name() {
-                "port" => ports.push(Port::parse(child)?),
-                _ => {}
+                "port" => {
+                    let port = PortRef::parse(child, ctx);
+                    ports.extend(ctx.or_skip(child, port)?);
+                }
+                "extraports" => {
+                    let extra = ExtraPorts::parse(child);
+                    extra_ports.extend(ctx.or_skip(child, extra)?);
+                }
+                _ => ctx.preserve(&mut unrecognized, child),
             }
         }
 
-        Ok(PortInfo { ports })
+        Ok(PortInfoRef {
+            ports,
+            extra_ports,
+            unrecognized,
+        })
     }
 
     ///Returns an iterator over the ports associated with this host.
-    pub fn ports(&self) -> std::slice::Iter<Port> {
+    pub fn ports(&self) -> std::slice::Iter<'_, PortRef<'a>> {
         self.ports.iter()
     }
+
+    ///Returns an iterator over the groups of ports that Nmap did not list
+    ///individually because they share a state.
+    pub fn extra_ports(&self) -> std::slice::Iter<'_, ExtraPorts> {
+        self.extra_ports.iter()
+    }
+
+    ///Returns content of the `ports` element that is not otherwise modeled.
+    pub fn unrecognized(&self) -> &Unrecognized {
+        &self.unrecognized
+    }
+
+    ///Copies the ports into a [`PortInfo`](struct.PortInfo.html).
+    pub fn to_owned(&self) -> PortInfo {
+        self.clone().into_owned()
+    }
+
+    ///Converts the ports into a [`PortInfo`](struct.PortInfo.html).
+    pub fn into_owned(self) -> PortInfo {
+        PortInfo {
+            ports: self.ports.into_iter().map(PortRef::into_owned).collect(),
+            extra_ports: self.extra_ports,
+            unrecognized: self.unrecognized,
+        }
+    }
 }
 
+///A port listed individually in the scan. Ports are ordered by protocol,
+///then by port number. Ties, e.g. between the same port of different hosts,
+///are broken by state, state reason, then service name, product and
+///version. Ports that differ only in other fields, such as their scripts,
+///are neither less nor greater than each other.
 #[derive(Clone, Debug, PartialEq)]
 pub struct Port {
     pub protocol: PortProtocol,
     pub port_number: u16,
     pub status: PortStatus,
     pub service_info: Option<ServiceInfo>,
+    pub scripts: Vec<Script>,
+    pub(crate) unrecognized: Unrecognized,
 }
 
 impl Port {
-    fn parse(node: Node) -> Result<Self, Error> {
-        let s = node
-            .attribute("protocol")
-            .ok_or_else(|| Error::from("expected `protocol` attribute in `port` node"))?;
-        let protocol =
-            PortProtocol::from_str(s).map_err(|_| Error::from("failed to parse port protocol"))?;
-
-        let port_number = node
-            .attribute("portid")
-            .ok_or_else(|| Error::from("expected `portid` attribute in `port` node"))
-            .and_then(|s| {
-                s.parse::<u16>()
-                    .map_err(|_| Error::from("failed to parse port ID"))
-            })?;
+    ///Returns content of the `port` element that is not otherwise modeled.
+    ///Empty unless
+    ///[`ParseOptions::preserve_unknown`](../struct.ParseOptions.html) is set.
+    pub fn unrecognized(&self) -> &Unrecognized {
+        &self.unrecognized
+    }
+
+    ///Returns the fields that ports are ordered and hashed by.
+    fn sort_key(&self) -> (&PortProtocol, u16, &PortState, &str, Option<ServiceKey<'_>>) {
+        let service = self
+            .service_info
+            .as_ref()
+            .map(|s| (s.name.as_str(), s.product.as_deref(), s.version.as_deref()));
+        (
+            &self.protocol,
+            self.port_number,
+            &self.status.state,
+            &self.status.reason,
+            service,
+        )
+    }
+}
+
+type ServiceKey<'a> = (&'a str, Option<&'a str>, Option<&'a str>);
+
+impl Eq for Port {}
+
+impl Ord for Port {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sort_key().cmp(&other.sort_key())
+    }
+}
+
+impl PartialOrd for Port {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Hash for Port {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.sort_key().hash(state);
+    }
+}
+
+///A [`Port`](struct.Port.html) whose strings are borrowed from the input
+///where possible.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PortRef<'a> {
+    pub protocol: PortProtocol,
+    pub port_number: u16,
+    pub status: PortStatusRef<'a>,
+    pub service_info: Option<ServiceInfoRef<'a>>,
+    pub scripts: Vec<ScriptRef<'a>>,
+    pub(crate) unrecognized: Unrecognized,
+}
+
+impl<'a> PortRef<'a> {
+    fn parse(node: Node<'_, 'a>, ctx: &mut ParseContext) -> Result<Self, Error> {
+        let protocol = parse_attribute(node, "protocol")?;
+        let port_number = parse_attribute(node, "portid")?;
+        let mut unrecognized = ctx.unrecognized(node, &["protocol", "portid"]);
 
         let mut status = None;
         let mut service_info = None;
+        let mut scripts = Vec::new();
 
         for child in node.children() {
             match child.tag_name().name() {
-                "state" => status = Some(PortStatus::parse(child)?),
-                "service" => service_info = Some(ServiceInfo::parse(child)?),
-                _ => {}
+                "state" => status = Some(PortStatusRef::parse(child, ctx)?),
+                "service" => {
+                    let service = ServiceInfoRef::parse(child, ctx);
+                    service_info = ctx.or_skip(child, service)?;
+                }
+                "script" => {
+                    let script = ScriptRef::parse(child, ctx);
+                    scripts.extend(ctx.or_skip(child, script)?);
+                }
+                _ => ctx.preserve(&mut unrecognized, child),
             }
         }
 
-        let status = status.ok_or_else(|| Error::from("expected `state` attribute for port"))?;
+        let status = status.ok_or_else(|| missing_element(node, "state"))?;
 
-        Ok(Port {
+        Ok(PortRef {
             protocol,
             port_number,
             status,
             service_info,
+            scripts,
+            unrecognized,
         })
     }
+
+    ///Returns content of the `port` element that is not otherwise modeled.
+    pub fn unrecognized(&self) -> &Unrecognized {
+        &self.unrecognized
+    }
+
+    ///Copies the port into a [`Port`](struct.Port.html).
+    pub fn to_owned(&self) -> Port {
+        self.clone().into_owned()
+    }
+
+    ///Converts the port into a [`Port`](struct.Port.html), copying only the
+    ///strings that are still borrowed.
+    pub fn into_owned(self) -> Port {
+        Port {
+            protocol: self.protocol,
+            port_number: self.port_number,
+            status: self.status.into_owned(),
+            service_info: self.service_info.map(ServiceInfoRef::into_owned),
+            scripts: self
+                .scripts
+                .into_iter()
+                .map(ScriptRef::into_owned)
+                .collect(),
+            unrecognized: self.unrecognized,
+        }
+    }
 }
 
-#[derive(EnumString, Display, Clone, Debug, PartialEq)]
+///Ports that were not listed individually, e.g. "Not shown: 995 closed
+///ports".
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExtraPorts {
+    pub state: PortState,
+    pub count: u32,
+    ///Why the ports are in `state`, e.g. 995 ports that responded with a
+    ///reset.
+    pub reasons: Vec<ExtraReason>,
+}
+
+impl ExtraPorts {
+    fn parse(node: Node) -> Result<Self, Error> {
+        let state = parse_attribute(node, "state")?;
+        let count = parse_attribute(node, "count")?;
+
+        let mut reasons = Vec::new();
+        for child in node.children() {
+            if child.tag_name().name() == "extrareasons" {
+                reasons.push(ExtraReason {
+                    reason: attribute(child, "reason")?.to_string(),
+                    count: parse_attribute(child, "count")?,
+                    protocol: parse_optional_attribute(child, "proto")?,
+                    ports: parse_optional_attribute(child, "ports")?,
+                });
+            }
+        }
+
+        Ok(ExtraPorts {
+            state,
+            count,
+            reasons,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExtraReason {
+    pub reason: String,
+    pub count: u32,
+    ///Protocol of the ports. Only written by Nmap 7.90 and later.
+    pub protocol: Option<PortProtocol>,
+    ///The ports themselves. Only written by Nmap 7.90 and later.
+    pub ports: Option<PortSet>,
+}
+
+///Protocol of a port. Protocols are ordered as in Nmap's port
+///specifications: TCP, UDP, SCTP, then IP.
+#[derive(EnumString, Display, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum PortProtocol {
-    #[strum(serialize = "ip")]
-    Ip,
     #[strum(serialize = "tcp")]
     Tcp,
     #[strum(serialize = "udp")]
     Udp,
     #[strum(serialize = "sctp")]
     Sctp,
+    #[strum(serialize = "ip")]
+    Ip,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -94,38 +311,72 @@ pub struct PortStatus {
     pub state: PortState,
     pub reason: String,
     pub reason_ttl: u8,
+    pub(crate) unrecognized: Unrecognized,
 }
 
 impl PortStatus {
-    fn parse(node: Node) -> Result<Self, Error> {
-        let s = node
-            .attribute("state")
-            .ok_or_else(|| Error::from("expected `state` attribute for port"))?;
-        let state =
-            PortState::from_str(s).map_err(|_| Error::from("failed to parse port state"))?;
-
-        let reason = node
-            .attribute("reason")
-            .ok_or_else(|| Error::from("expected `reason` attribute for port"))?
-            .to_string();
-
-        let reason_ttl = node
-            .attribute("reason_ttl")
-            .ok_or_else(|| Error::from("expected `reason_ttl` attribute for port"))
-            .and_then(|s| {
-                s.parse::<u8>()
-                    .map_err(|_| Error::from("failed to parse port reason_ttl"))
-            })?;
-
-        Ok(PortStatus {
+    ///Returns content of the `state` element that is not otherwise modeled.
+    ///Empty unless
+    ///[`ParseOptions::preserve_unknown`](../struct.ParseOptions.html) is set.
+    pub fn unrecognized(&self) -> &Unrecognized {
+        &self.unrecognized
+    }
+}
+
+///A [`PortStatus`](struct.PortStatus.html) borrowed from the input where
+///possible.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PortStatusRef<'a> {
+    pub state: PortState,
+    pub reason: Cow<'a, str>,
+    pub reason_ttl: u8,
+    pub(crate) unrecognized: Unrecognized,
+}
+
+impl<'a> PortStatusRef<'a> {
+    fn parse(node: Node<'_, 'a>, ctx: &mut ParseContext) -> Result<Self, Error> {
+        let state = parse_attribute(node, "state")?;
+        let reason = borrowed_attribute(node, "reason")?;
+
+        let reason_ttl = parse_attribute(node, "reason_ttl");
+        let reason_ttl = ctx.or_default(node, reason_ttl, || 0)?;
+
+        let mut unrecognized = ctx.unrecognized(node, &["state", "reason", "reason_ttl"]);
+        for child in node.children() {
+            ctx.preserve(&mut unrecognized, child);
+        }
+
+        Ok(PortStatusRef {
             state,
             reason,
             reason_ttl,
+            unrecognized,
         })
     }
+
+    ///Returns content of the `state` element that is not otherwise modeled.
+    pub fn unrecognized(&self) -> &Unrecognized {
+        &self.unrecognized
+    }
+
+    ///Copies the status into a [`PortStatus`](struct.PortStatus.html).
+    pub fn to_owned(&self) -> PortStatus {
+        self.clone().into_owned()
+    }
+
+    ///Converts the status into a [`PortStatus`](struct.PortStatus.html).
+    pub fn into_owned(self) -> PortStatus {
+        PortStatus {
+            state: self.state,
+            reason: self.reason.into_owned(),
+            reason_ttl: self.reason_ttl,
+            unrecognized: self.unrecognized,
+        }
+    }
 }
 
-#[derive(EnumString, Display, Clone, Debug, PartialEq)]
+///State of a port, as named by Nmap's `statenum2str()`.
+#[derive(EnumString, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum PortState {
     #[strum(serialize = "open")]
     Open,
@@ -135,46 +386,163 @@ pub enum PortState {
     Filtered,
     #[strum(serialize = "unfiltered")]
     Unfiltered,
+    ///No response, e.g. to a UDP probe, so the port may be open or
+    ///filtered.
     #[strum(serialize = "open|filtered")]
     OpenFiltered,
-    #[strum(serialize = "close|filtered")]
-    CloseFiltered,
+    ///The port may be closed or filtered. Only emitted by idle scans.
+    #[strum(serialize = "closed|filtered")]
+    ClosedFiltered,
+    ///Any other `state` value, kept verbatim. Nmap itself writes `unknown`
+    ///for a state it cannot name.
+    #[strum(default = "true")]
+    Unknown(String),
+}
+
+impl PortState {
+    ///Returns true if the port is known to be open.
+    pub fn is_open(&self) -> bool {
+        *self == PortState::Open
+    }
+
+    ///Returns true if the port may be open, i.e. it is `open` or
+    ///`open|filtered`.
+    ///
+    ///UDP and other connectionless scans report most open ports as
+    ///`open|filtered`, because an open service often does not answer an
+    ///empty probe. Such ports are worth a version scan (`-sV`) to tell
+    ///them apart.
+    pub fn is_open_ish(&self) -> bool {
+        matches!(self, PortState::Open | PortState::OpenFiltered)
+    }
+
+    ///Returns true if the port is known to be closed.
+    pub fn is_closed(&self) -> bool {
+        *self == PortState::Closed
+    }
+
+    ///Returns true if Nmap could not tell whether the port is open or
+    ///closed, i.e. it is `open|filtered`, `closed|filtered` or `unfiltered`.
+    pub fn is_ambiguous(&self) -> bool {
+        matches!(
+            self,
+            PortState::Unfiltered | PortState::OpenFiltered | PortState::ClosedFiltered
+        )
+    }
+}
+
+///Formats the state as Nmap does, e.g. `open|filtered`.
+impl fmt::Display for PortState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            PortState::Open => "open",
+            PortState::Closed => "closed",
+            PortState::Filtered => "filtered",
+            PortState::Unfiltered => "unfiltered",
+            PortState::OpenFiltered => "open|filtered",
+            PortState::ClosedFiltered => "closed|filtered",
+            PortState::Unknown(s) => s,
+        };
+        write!(f, "{}", s)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct ServiceInfo {
     pub name: String,
+    ///Name of the software, e.g. `OpenSSH`. Only present when the version
+    ///was detected.
+    pub product: Option<String>,
+    pub version: Option<String>,
+    ///Extra information about the service, e.g. `protocol 2.0`.
+    pub extrainfo: Option<String>,
     pub confidence_level: u8,
     pub method: ServiceMethod,
+    pub cpe: Vec<Cpe>,
+    ///`cpe` elements whose text is not a valid CPE name, verbatim.
+    pub unparsed_cpe: Vec<String>,
+    pub(crate) unrecognized: Unrecognized,
 }
 
 impl ServiceInfo {
-    fn parse(node: Node) -> Result<Self, Error> {
-        let name = node
-            .attribute("name")
-            .ok_or_else(|| Error::from("expected `name` attribute for service"))?
-            .to_string();
-
-        let confidence_level = node
-            .attribute("conf")
-            .ok_or_else(|| Error::from("expected `conf` attribute for service"))
-            .and_then(|s| {
-                s.parse::<u8>()
-                    .map_err(|_| Error::from("failed to parse port reason_ttl"))
-            })?;
-
-        let s = node
-            .attribute("method")
-            .ok_or_else(|| Error::from("expected `method` attribute for service"))?;
-        let method = ServiceMethod::from_str(s)
-            .map_err(|_| Error::from("failed to parse service method"))?;
-
-        Ok(ServiceInfo {
+    ///Returns content of the `service` element that is not otherwise modeled.
+    ///Empty unless
+    ///[`ParseOptions::preserve_unknown`](../struct.ParseOptions.html) is set.
+    pub fn unrecognized(&self) -> &Unrecognized {
+        &self.unrecognized
+    }
+}
+
+///A [`ServiceInfo`](struct.ServiceInfo.html) whose strings are borrowed
+///from the input where possible.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ServiceInfoRef<'a> {
+    pub name: Cow<'a, str>,
+    pub product: Option<Cow<'a, str>>,
+    pub version: Option<Cow<'a, str>>,
+    pub extrainfo: Option<Cow<'a, str>>,
+    pub confidence_level: u8,
+    pub method: ServiceMethod,
+    pub cpe: Vec<Cpe>,
+    pub unparsed_cpe: Vec<String>,
+    pub(crate) unrecognized: Unrecognized,
+}
+
+impl<'a> ServiceInfoRef<'a> {
+    fn parse(node: Node<'_, 'a>, ctx: &ParseContext) -> Result<Self, Error> {
+        let name = borrowed_attribute(node, "name")?;
+        let text = |attr| borrowed_optional_attribute(node, attr);
+        let confidence_level = parse_attribute(node, "conf")?;
+        let method = parse_attribute(node, "method")?;
+        let (cpe, unparsed_cpe) = parse_cpe_nodes(node);
+
+        let mut unrecognized = ctx.unrecognized(
+            node,
+            &["name", "product", "version", "extrainfo", "conf", "method"],
+        );
+        for child in node.children() {
+            if child.tag_name().name() != "cpe" {
+                ctx.preserve(&mut unrecognized, child);
+            }
+        }
+
+        Ok(ServiceInfoRef {
             name,
+            product: text("product"),
+            version: text("version"),
+            extrainfo: text("extrainfo"),
             confidence_level,
             method,
+            cpe,
+            unparsed_cpe,
+            unrecognized,
         })
     }
+
+    ///Returns content of the `service` element that is not otherwise modeled.
+    pub fn unrecognized(&self) -> &Unrecognized {
+        &self.unrecognized
+    }
+
+    ///Copies the service into a [`ServiceInfo`](struct.ServiceInfo.html).
+    pub fn to_owned(&self) -> ServiceInfo {
+        self.clone().into_owned()
+    }
+
+    ///Converts the service into a [`ServiceInfo`](struct.ServiceInfo.html).
+    pub fn into_owned(self) -> ServiceInfo {
+        ServiceInfo {
+            name: self.name.into_owned(),
+            product: self.product.map(Cow::into_owned),
+            version: self.version.map(Cow::into_owned),
+            extrainfo: self.extrainfo.map(Cow::into_owned),
+            confidence_level: self.confidence_level,
+            method: self.method,
+            cpe: self.cpe,
+            unparsed_cpe: self.unparsed_cpe,
+            unrecognized: self.unrecognized,
+        }
+    }
 }
 
 #[derive(EnumString, Display, Clone, Debug, PartialEq)]
@@ -184,3 +552,78 @@ pub enum ServiceMethod {
     #[strum(serialize = "probed")]
     Probe,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::NmapResults;
+
+    #[test]
+    fn port_states() {
+        let xml = r#"<nmaprun start="1588318812">
+<host><status state="up" reason="user-set" reason_ttl="0"/>
+<address addr="192.0.2.1" addrtype="ipv4"/>
+<ports>
+<extraports state="closed|filtered" count="997"><extrareasons reason="no-ipid-change" count="997"/></extraports>
+<port protocol="tcp" portid="22"><state state="open" reason="ipid-change" reason_ttl="0"/></port>
+<port protocol="tcp" portid="25"><state state="open|filtered" reason="no-response" reason_ttl="0"/></port>
+<port protocol="tcp" portid="80"><state state="unknown" reason="unknown" reason_ttl="0"/></port>
+</ports>
+</host>
+</nmaprun>"#;
+        let results = NmapResults::parse(xml).unwrap();
+        let host = results.hosts().next().unwrap();
+
+        let extra = host.port_info.extra_ports().next().unwrap();
+        assert_eq!(extra.state, PortState::ClosedFiltered);
+        assert!(extra.state.is_ambiguous() && !extra.state.is_open_ish());
+
+        let states = host
+            .port_info
+            .ports()
+            .map(|p| &p.status.state)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            states,
+            vec![
+                &PortState::Open,
+                &PortState::OpenFiltered,
+                &PortState::Unknown("unknown".to_string())
+            ]
+        );
+        assert!(states[0].is_open() && states[0].is_open_ish());
+        assert!(!states[1].is_open() && states[1].is_open_ish());
+        assert!(!states[2].is_open_ish() && !states[2].is_ambiguous());
+
+        for state in &["open|filtered", "closed|filtered", "unknown"] {
+            assert_eq!(state.parse::<PortState>().unwrap().to_string(), *state);
+        }
+    }
+
+    #[test]
+    fn port_order() {
+        let xml = r#"<nmaprun start="1588318812">
+<host><status state="up" reason="user-set" reason_ttl="0"/>
+<address addr="192.0.2.1" addrtype="ipv4"/>
+<ports>
+<port protocol="udp" portid="53"><state state="open" reason="udp-response" reason_ttl="64"/></port>
+<port protocol="tcp" portid="80"><state state="open" reason="syn-ack" reason_ttl="64"/><service name="http" method="table" conf="3"/></port>
+<port protocol="tcp" portid="80"><state state="open" reason="syn-ack" reason_ttl="64"/><service name="http" product="nginx" method="probed" conf="10"/></port>
+<port protocol="tcp" portid="80"><state state="closed" reason="reset" reason_ttl="64"/></port>
+<port protocol="tcp" portid="22"><state state="open" reason="syn-ack" reason_ttl="64"/></port>
+</ports>
+</host>
+</nmaprun>"#;
+        let results = NmapResults::parse(xml).unwrap();
+        let ports = &results.hosts().next().unwrap().port_info.ports;
+
+        let mut sorted = ports.iter().collect::<Vec<_>>();
+        sorted.sort();
+        assert_eq!(
+            sorted,
+            vec![&ports[4], &ports[1], &ports[2], &ports[3], &ports[0]]
+        );
+        assert!(ports[1] != ports[2] && ports[1].cmp(&ports[2]) != Ordering::Equal);
+        assert_eq!(ports[1].cmp(&ports[1].clone()), Ordering::Equal);
+    }
+}
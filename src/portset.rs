@@ -0,0 +1,291 @@
+//!Sets of port numbers, such as the ports a scan probed.
+//!
+//!```
+//!use nmap_xml_parser::portset::PortSet;
+//!
+//!let scanned: PortSet = "1,3-4,6-7,9".parse().unwrap();
+//!let listed: PortSet = vec![4, 9, 8443].into_iter().collect();
+//!
+//!assert!(scanned.contains(3));
+//!assert_eq!(scanned.len(), 6);
+//!assert_eq!(scanned.intersection(&listed).to_string(), "4,9");
+//!assert_eq!(scanned.union(&listed).to_string(), "1,3-4,6-7,9,8443");
+//!```
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+///A set of port numbers, stored as sorted ranges.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PortSet {
+    ///Non-empty, sorted ranges that neither overlap nor touch.
+    ranges: Vec<RangeInclusive<u16>>,
+}
+
+impl PortSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///Adds `port` to the set.
+    pub fn insert(&mut self, port: u16) {
+        self.insert_range(port..=port);
+    }
+
+    ///Adds the ports in `range` to the set.
+    pub fn insert_range(&mut self, range: RangeInclusive<u16>) {
+        if range.is_empty() {
+            return;
+        }
+        let i = self.ranges.partition_point(|r| r.start() < range.start());
+        self.ranges.insert(i, range);
+        self.normalize();
+    }
+
+    ///Returns true if `port` is in the set.
+    pub fn contains(&self, port: u16) -> bool {
+        let i = self.ranges.partition_point(|r| *r.end() < port);
+        self.ranges.get(i).is_some_and(|r| r.contains(&port))
+    }
+
+    ///Returns the number of ports in the set.
+    pub fn len(&self) -> usize {
+        self.ranges
+            .iter()
+            .map(|r| (*r.end() - *r.start()) as usize + 1)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    ///Returns the ports that are in either set.
+    pub fn union(&self, other: &PortSet) -> PortSet {
+        let mut ranges = self.ranges.clone();
+        ranges.extend(other.ranges.iter().cloned());
+        ranges.sort_by_key(|r| *r.start());
+
+        let mut r = PortSet { ranges };
+        r.normalize();
+        r
+    }
+
+    ///Returns the ports that are in both sets.
+    pub fn intersection(&self, other: &PortSet) -> PortSet {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (&self.ranges[i], &other.ranges[j]);
+            let start = *a.start().max(b.start());
+            let end = *a.end().min(b.end());
+            if start <= end {
+                ranges.push(start..=end);
+            }
+            if a.end() < b.end() {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        PortSet { ranges }
+    }
+
+    ///Returns the ports that are in this set but not in `other`.
+    pub fn difference(&self, other: &PortSet) -> PortSet {
+        let mut ranges = Vec::new();
+        let mut j = 0;
+        for range in &self.ranges {
+            let mut start = u32::from(*range.start());
+            let end = u32::from(*range.end());
+            while j < other.ranges.len() && *other.ranges[j].end() < *range.start() {
+                j += 1;
+            }
+
+            let mut k = j;
+            while start <= end && k < other.ranges.len() {
+                let hole = &other.ranges[k];
+                if u32::from(*hole.start()) > end {
+                    break;
+                }
+                if u32::from(*hole.start()) > start {
+                    ranges.push(start as u16..=*hole.start() - 1);
+                }
+                start = start.max(u32::from(*hole.end()) + 1);
+                k += 1;
+            }
+            if start <= end {
+                ranges.push(start as u16..=end as u16);
+            }
+        }
+
+        PortSet { ranges }
+    }
+
+    ///Returns an iterator over the ports in the set, in ascending order.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            ranges: self.ranges.iter(),
+            current: None,
+        }
+    }
+
+    ///Returns an iterator over the ranges of ports in the set, in ascending
+    ///order. Ranges neither overlap nor touch.
+    pub fn ranges(&self) -> std::slice::Iter<'_, RangeInclusive<u16>> {
+        self.ranges.iter()
+    }
+
+    ///Merges overlapping and touching ranges, which must be sorted by start.
+    fn normalize(&mut self) {
+        let mut merged: Vec<RangeInclusive<u16>> = Vec::with_capacity(self.ranges.len());
+        for range in self.ranges.drain(..) {
+            match merged.last_mut() {
+                Some(last) if u32::from(*range.start()) <= u32::from(*last.end()) + 1 => {
+                    if range.end() > last.end() {
+                        *last = *last.start()..=*range.end();
+                    }
+                }
+                _ => merged.push(range),
+            }
+        }
+        self.ranges = merged;
+    }
+}
+
+impl FromIterator<u16> for PortSet {
+    fn from_iter<I: IntoIterator<Item = u16>>(iter: I) -> Self {
+        let mut ports = iter.into_iter().collect::<Vec<_>>();
+        ports.sort_unstable();
+
+        let mut r = PortSet {
+            ranges: ports.into_iter().map(|p| p..=p).collect(),
+        };
+        r.normalize();
+        r
+    }
+}
+
+impl<'a> IntoIterator for &'a PortSet {
+    type Item = u16;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+///Iterator over the ports in a [`PortSet`](struct.PortSet.html).
+#[derive(Clone, Debug)]
+pub struct Iter<'a> {
+    ranges: std::slice::Iter<'a, RangeInclusive<u16>>,
+    current: Option<RangeInclusive<u16>>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = u16;
+
+    fn next(&mut self) -> Option<u16> {
+        loop {
+            if let Some(port) = self.current.as_mut().and_then(Iterator::next) {
+                return Some(port);
+            }
+            self.current = Some(self.ranges.next()?.clone());
+        }
+    }
+}
+
+///Formats the set as Nmap does, e.g. `1,3-4,6-7`.
+impl fmt::Display for PortSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, range) in self.ranges.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            if range.start() == range.end() {
+                write!(f, "{}", range.start())?;
+            } else {
+                write!(f, "{}-{}", range.start(), range.end())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+///Error returned when a [`PortSet`](struct.PortSet.html) cannot be parsed.
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+#[error("invalid port range `{0}`")]
+pub struct ParsePortSetError(String);
+
+///Parses a list of ports and ranges as in `scaninfo/@services`, e.g.
+///`1,3-4,6-7`. An empty string is an empty set.
+impl FromStr for PortSet {
+    type Err = ParsePortSetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut ranges = Vec::new();
+        for part in s.split(',').filter(|p| !p.is_empty()) {
+            let error = || ParsePortSetError(part.to_string());
+            let (start, end) = match part.find('-') {
+                Some(i) => (&part[..i], &part[i + 1..]),
+                None => (part, part),
+            };
+            let start = start.parse::<u16>().map_err(|_| error())?;
+            let end = end.parse::<u16>().map_err(|_| error())?;
+            if start > end {
+                return Err(error());
+            }
+            ranges.push(start..=end);
+        }
+        ranges.sort_by_key(|r| *r.start());
+
+        let mut r = PortSet { ranges };
+        r.normalize();
+        Ok(r)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn set(s: &str) -> PortSet {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_and_display() {
+        assert_eq!(set("").to_string(), "");
+        assert_eq!(set("9,1,2-3,5-7,4").to_string(), "1-7,9");
+        assert_eq!(set("0-65535").len(), 65536);
+        assert!("1-".parse::<PortSet>().is_err());
+        assert!("3-1".parse::<PortSet>().is_err());
+        assert!("65536".parse::<PortSet>().is_err());
+    }
+
+    #[test]
+    fn operations() {
+        let a = set("1-10,20-30,65535");
+        let b = set("5-25,40,65535");
+
+        assert_eq!(a.union(&b).to_string(), "1-30,40,65535");
+        assert_eq!(a.intersection(&b).to_string(), "5-10,20-25,65535");
+        assert_eq!(a.difference(&b).to_string(), "1-4,26-30");
+        assert_eq!(b.difference(&a).to_string(), "11-19,40");
+        assert_eq!(a.difference(&PortSet::new()), a);
+
+        assert!(a.contains(10) && a.contains(65535));
+        assert!(!a.contains(11) && !a.contains(0));
+        assert_eq!(set("1-3,7").iter().collect::<Vec<_>>(), vec![1, 2, 3, 7]);
+
+        let mut c = PortSet::new();
+        c.insert(3);
+        c.insert(1);
+        c.insert_range(2..=2);
+        c.insert_range(65534..=65535);
+        assert_eq!(c.to_string(), "1-3,65534-65535");
+        assert_eq!(c, vec![65535, 2, 1, 3, 65534].into_iter().collect());
+    }
+}
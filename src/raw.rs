@@ -0,0 +1,179 @@
+//!Generic representation of XML the parser does not model.
+//!
+//!When [`ParseOptions::preserve_unknown`](../struct.ParseOptions.html) is
+//!set, elements and attributes that would otherwise be discarded are kept as
+//![`RawElement`](struct.RawElement.html) trees so that output from newer
+//!Nmap versions is not lost and can be written back out.
+//!
+//!Unknown content is kept on the `nmaprun`, `host`, `status`, `ports`,
+//!`port`, `state`, `service`, `script`, `osmatch` and `osclass` elements,
+//!each of which has an `unrecognized()` method. Unknown attributes and
+//!children of the other elements, e.g. `address`, `hostname`, `times`,
+//!`extraports` and `runstats`, and the children of `os` other than
+//!`osmatch`, e.g. `portused`, are still discarded.
+use roxmltree::Node;
+use std::fmt;
+
+///An XML attribute.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RawAttribute {
+    pub name: String,
+    pub value: String,
+}
+
+///An XML element and everything below it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RawElement {
+    pub name: String,
+    pub attributes: Vec<RawAttribute>,
+    ///Text directly inside the element, if it is not only whitespace.
+    pub text: Option<String>,
+    pub children: Vec<RawElement>,
+}
+
+impl RawElement {
+    pub(crate) fn from_node(node: Node) -> Self {
+        let mut text = String::new();
+        let mut children = Vec::new();
+
+        for child in node.children() {
+            if child.is_element() {
+                children.push(RawElement::from_node(child));
+            } else if child.is_text() {
+                text.push_str(child.text().unwrap_or(""));
+            }
+        }
+
+        RawElement {
+            name: node.tag_name().name().to_string(),
+            attributes: node
+                .attributes()
+                .iter()
+                .map(|a| RawAttribute {
+                    name: a.name().to_string(),
+                    value: a.value().to_string(),
+                })
+                .collect(),
+            text: if text.trim().is_empty() {
+                None
+            } else {
+                Some(text)
+            },
+            children,
+        }
+    }
+
+    ///Returns the value of the attribute called `name`, if present.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|a| a.name == name)
+            .map(|a| a.value.as_str())
+    }
+}
+
+impl fmt::Display for RawElement {
+    ///Writes the element back out as XML.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<{}", self.name)?;
+        for a in &self.attributes {
+            write!(f, " {}=\"{}\"", a.name, escape(&a.value, true))?;
+        }
+
+        if self.text.is_none() && self.children.is_empty() {
+            return write!(f, "/>");
+        }
+
+        write!(f, ">")?;
+        if let Some(text) = &self.text {
+            write!(f, "{}", escape(text, false))?;
+        }
+        for child in &self.children {
+            write!(f, "{}", child)?;
+        }
+        write!(f, "</{}>", self.name)
+    }
+}
+
+///Elements and attributes of a node that the parser does not model.
+///
+///This is always empty unless
+///[`ParseOptions::preserve_unknown`](../struct.ParseOptions.html) is set.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Unrecognized {
+    pub attributes: Vec<RawAttribute>,
+    pub elements: Vec<RawElement>,
+}
+
+impl Unrecognized {
+    ///Returns true if nothing was preserved.
+    pub fn is_empty(&self) -> bool {
+        self.attributes.is_empty() && self.elements.is_empty()
+    }
+
+    ///Returns the first preserved element called `name`, if any.
+    pub fn element(&self, name: &str) -> Option<&RawElement> {
+        self.elements.iter().find(|e| e.name == name)
+    }
+
+    ///Returns the value of the preserved attribute called `name`, if any.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|a| a.name == name)
+            .map(|a| a.value.as_str())
+    }
+}
+
+///Escapes `s` for use as XML text, or as an attribute value if `attribute`
+///is true.
+pub(crate) fn escape(s: &str, attribute: bool) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' if attribute => out.push_str("&quot;"),
+            '\n' if attribute => out.push_str("&#xa;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use roxmltree::Document;
+
+    #[test]
+    fn round_trip() {
+        let xml = r#"<trace port="80" proto="tcp"><hop ttl="1" ipaddr="10.0.0.1" rtt="0.52"/><note>a &amp; b</note></trace>"#;
+        let doc = Document::parse(xml).unwrap();
+        let raw = RawElement::from_node(doc.root_element());
+
+        assert_eq!(raw.attribute("proto"), Some("tcp"));
+        assert_eq!(raw.children.len(), 2);
+        assert_eq!(raw.children[1].text.as_deref(), Some("a & b"));
+        assert_eq!(raw.to_string(), xml);
+    }
+
+    #[test]
+    fn escapes_attribute_values() {
+        let raw = RawElement {
+            name: "script".to_string(),
+            attributes: vec![RawAttribute {
+                name: "output".to_string(),
+                value: "\n  <\"x\">".to_string(),
+            }],
+            text: None,
+            children: Vec::new(),
+        };
+
+        assert_eq!(
+            raw.to_string(),
+            r#"<script output="&#xa;  &lt;&quot;x&quot;&gt;"/>"#
+        );
+    }
+}
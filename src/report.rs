@@ -0,0 +1,74 @@
+//!Human-readable reports of scan results.
+//!
+//!Each submodule renders a whole [`NmapResults`](../struct.NmapResults.html)
+//!with `render()` and the part of the report for a single
+//![`Host`](../host/struct.Host.html) with `render_host()`.
+use crate::port::ServiceInfo;
+
+pub mod html;
+pub mod markdown;
+pub mod normal;
+
+///Returns the product, version and extra information of `service` as Nmap
+///prints them in the VERSION column, e.g. `Apache httpd 2.4.7 ((Ubuntu))`.
+pub(crate) fn service_version(service: &ServiceInfo) -> Option<String> {
+    let mut r = [&service.product, &service.version]
+        .iter()
+        .filter_map(|s| s.as_deref())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    if let Some(extrainfo) = &service.extrainfo {
+        if !r.is_empty() {
+            r.push(' ');
+        }
+        r.push_str(&format!("({})", extrainfo));
+    }
+
+    if r.is_empty() {
+        None
+    } else {
+        Some(r)
+    }
+}
+
+///Formats seconds since the Unix epoch as e.g. `2020-06-13 10:21:07 UTC`.
+pub(crate) fn format_timestamp(t: i64) -> String {
+    let days = t.div_euclid(86_400);
+    let secs = t.rem_euclid(86_400);
+
+    //Converts days since 1970-01-01 to a proleptic Gregorian date. See
+    //http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn timestamps() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00:00 UTC");
+        assert_eq!(format_timestamp(1592043667), "2020-06-13 10:21:07 UTC");
+        assert_eq!(format_timestamp(951782400), "2000-02-29 00:00:00 UTC");
+        assert_eq!(format_timestamp(-1), "1969-12-31 23:59:59 UTC");
+    }
+}
@@ -0,0 +1,202 @@
+//!Renders scan results as a self-contained HTML page.
+//!
+//!The page has no external dependencies. Host and port tables can be sorted
+//!by clicking a column header, and script output is collapsed by default.
+//!
+//!```
+//!# use std::path::PathBuf;
+//!# use std::fs;
+//!use nmap_xml_parser::NmapResults;
+//!use nmap_xml_parser::report::html;
+//!# let mut nmap_xml_file = PathBuf::new();
+//!# nmap_xml_file.push(&std::env::var("CARGO_MANIFEST_DIR").unwrap());
+//!# nmap_xml_file.push("tests/scanme-version.xml");
+//!let content = fs::read_to_string(nmap_xml_file).unwrap();
+//!let results = NmapResults::parse(&content).unwrap();
+//!
+//!let page = html::render(&results);
+//!assert!(page.starts_with("<!DOCTYPE html>"));
+//!```
+use crate::host::{Host, HostState, Script};
+use crate::port::PortState;
+use crate::raw::escape;
+use crate::report::{format_timestamp, service_version};
+use crate::NmapResults;
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; color: #222; }
+table { border-collapse: collapse; margin: 1em 0; }
+th, td { border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; vertical-align: top; }
+th { background: #eee; cursor: pointer; user-select: none; }
+th[aria-sort=ascending]::after { content: \" \\25B2\"; }
+th[aria-sort=descending]::after { content: \" \\25BC\"; }
+tr.open td.state { color: #080; font-weight: bold; }
+summary { cursor: pointer; font-family: monospace; }
+pre { background: #f6f6f6; padding: 0.5em; overflow-x: auto; }
+";
+
+const SCRIPT: &str = "
+document.querySelectorAll(\"table.sortable th\").forEach(function (th) {
+  th.addEventListener(\"click\", function () {
+    var table = th.closest(\"table\");
+    var body = table.tBodies[0];
+    var index = Array.prototype.indexOf.call(th.parentNode.children, th);
+    var ascending = th.getAttribute(\"aria-sort\") !== \"ascending\";
+    th.parentNode.querySelectorAll(\"th\").forEach(function (h) {
+      h.removeAttribute(\"aria-sort\");
+    });
+    th.setAttribute(\"aria-sort\", ascending ? \"ascending\" : \"descending\");
+    var key = function (row) {
+      var cell = row.cells[index];
+      return cell.getAttribute(\"data-sort\") || cell.textContent;
+    };
+    Array.prototype.slice.call(body.rows)
+      .sort(function (a, b) {
+        var r = key(a).localeCompare(key(b), undefined, { numeric: true });
+        return ascending ? r : -r;
+      })
+      .forEach(function (row) { body.appendChild(row); });
+  });
+});
+";
+
+///Renders `results` as an HTML page.
+pub fn render(results: &NmapResults) -> String {
+    let stats = results.stats();
+    let count = |state| stats.hosts_by_state.get(&state).copied().unwrap_or(0);
+
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str("<title>Nmap scan report</title>\n");
+    out.push_str(&format!("<style>{}</style>\n", STYLE));
+    out.push_str("</head>\n<body>\n<h1>Nmap scan report</h1>\n");
+
+    let mut summary = format!("Scan started {}", format_timestamp(results.scan_start_time));
+    if let Some(end) = results.scan_end_time {
+        summary.push_str(&format!(", finished {}", format_timestamp(end)));
+    }
+    out.push_str(&format!(
+        "<p>{}. Hosts: {} up, {} down.</p>\n",
+        summary,
+        count(HostState::Up),
+        count(HostState::Down)
+    ));
+
+    out.push_str("<h2>Hosts</h2>\n<table class=\"sortable\">\n");
+    out.push_str("<thead><tr><th>Address</th><th>Name</th><th>State</th><th>Open ports</th></tr></thead>\n<tbody>\n");
+    for (i, (host, open)) in stats.open_ports.iter().enumerate() {
+        let address = escape(&host.display_address().unwrap_or_default(), false);
+        let address = if host.status.state == HostState::Up {
+            format!("<a href=\"#host-{}\">{}</a>", i + 1, address)
+        } else {
+            address
+        };
+        out.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            address,
+            escape(host.primary_name().unwrap_or_default(), false),
+            host.status.state,
+            open
+        ));
+    }
+    out.push_str("</tbody>\n</table>\n");
+
+    for (i, host) in results.hosts().enumerate() {
+        if host.status.state == HostState::Up {
+            out.push_str(&render_host(host, &format!("host-{}", i + 1)));
+        }
+    }
+
+    out.push_str(&format!("<script>{}</script>\n</body>\n</html>\n", SCRIPT));
+    out
+}
+
+///Renders the section of the page for `host`, with `id` as its anchor.
+pub fn render_host(host: &Host, id: &str) -> String {
+    let mut out = String::new();
+    let address = host.display_address().unwrap_or_default();
+    let title = match host.primary_name() {
+        Some(name) => format!("{} ({})", address, name),
+        None => address,
+    };
+
+    out.push_str(&format!("<section id=\"{}\">\n", escape(id, true)));
+    out.push_str(&format!("<h2>{}</h2>\n", escape(&title, false)));
+
+    let extra = host
+        .port_info
+        .extra_ports()
+        .map(|e| format!("{} {}", e.count, e.state))
+        .collect::<Vec<_>>();
+    if !extra.is_empty() {
+        out.push_str(&format!("<p>Not shown: {} ports.</p>\n", extra.join(", ")));
+    }
+
+    let ports = host.port_info.ports().collect::<Vec<_>>();
+    if !ports.is_empty() {
+        out.push_str("<table class=\"sortable\">\n");
+        out.push_str("<thead><tr><th>Port</th><th>Protocol</th><th>State</th><th>Service</th><th>Version</th></tr></thead>\n<tbody>\n");
+        for port in &ports {
+            let service = port.service_info.as_ref();
+            let class = if port.status.state == PortState::Open {
+                " class=\"open\""
+            } else {
+                ""
+            };
+            out.push_str(&format!(
+                "<tr{}><td>{}</td><td>{}</td><td class=\"state\">{}</td><td>{}</td><td>{}</td></tr>\n",
+                class,
+                port.port_number,
+                port.protocol,
+                escape(&port.status.state.to_string(), false),
+                escape(service.map(|s| s.name.as_str()).unwrap_or_default(), false),
+                escape(&service.and_then(service_version).unwrap_or_default(), false)
+            ));
+        }
+        out.push_str("</tbody>\n</table>\n");
+    }
+
+    let scripts = ports
+        .iter()
+        .flat_map(|p| {
+            p.scripts
+                .iter()
+                .map(move |s| (Some(format!("{}/{}", p.port_number, p.protocol)), s))
+        })
+        .chain(host.scripts().map(|s| (None, s)))
+        .collect::<Vec<_>>();
+    if !scripts.is_empty() {
+        out.push_str("<h3>Script output</h3>\n");
+        for (port, script) in scripts {
+            render_script(&mut out, port.as_deref(), script);
+        }
+    }
+
+    let os_matches = host.os_matches().collect::<Vec<_>>();
+    if !os_matches.is_empty() {
+        out.push_str("<h3>OS detection</h3>\n<ul>\n");
+        for m in os_matches {
+            out.push_str(&format!(
+                "<li>{} ({}%)</li>\n",
+                escape(&m.name, false),
+                m.accuracy
+            ));
+        }
+        out.push_str("</ul>\n");
+    }
+
+    out.push_str("</section>\n");
+    out
+}
+
+fn render_script(out: &mut String, port: Option<&str>, script: &Script) {
+    let label = match port {
+        Some(port) => format!("{} {}", port, script.id),
+        None => script.id.clone(),
+    };
+    out.push_str(&format!(
+        "<details><summary>{}</summary><pre>{}</pre></details>\n",
+        escape(&label, false),
+        escape(script.output.trim_matches('\n'), false)
+    ));
+}
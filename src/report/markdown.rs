@@ -0,0 +1,177 @@
+//!Renders scan results as a Markdown document.
+//!
+//!```
+//!# use std::path::PathBuf;
+//!# use std::fs;
+//!use nmap_xml_parser::NmapResults;
+//!use nmap_xml_parser::report::markdown;
+//!# let mut nmap_xml_file = PathBuf::new();
+//!# nmap_xml_file.push(&std::env::var("CARGO_MANIFEST_DIR").unwrap());
+//!# nmap_xml_file.push("tests/scanme-version.xml");
+//!let content = fs::read_to_string(nmap_xml_file).unwrap();
+//!let results = NmapResults::parse(&content).unwrap();
+//!
+//!let document = markdown::render(&results);
+//!assert!(document.starts_with("# Nmap scan report\n"));
+//!```
+use crate::host::{Host, HostState, Script};
+use crate::report::{format_timestamp, service_version};
+use crate::NmapResults;
+
+///Renders `results` as a Markdown document.
+pub fn render(results: &NmapResults) -> String {
+    let stats = results.stats();
+    let count = |state| stats.hosts_by_state.get(&state).copied().unwrap_or(0);
+
+    let mut out = String::from("# Nmap scan report\n\n");
+
+    out.push_str(&format!(
+        "Scan started {}",
+        format_timestamp(results.scan_start_time)
+    ));
+    if let Some(end) = results.scan_end_time {
+        out.push_str(&format!(", finished {}", format_timestamp(end)));
+    }
+    out.push_str(&format!(
+        ". Hosts: {} up, {} down.\n\n",
+        count(HostState::Up),
+        count(HostState::Down)
+    ));
+
+    out.push_str("| Address | Name | State | Open ports |\n");
+    out.push_str("| --- | --- | --- | --: |\n");
+    for (host, open) in &stats.open_ports {
+        out.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            cell(&host.display_address().unwrap_or_default()),
+            cell(host.primary_name().unwrap_or_default()),
+            host.status.state,
+            open
+        ));
+    }
+
+    for host in results.hosts() {
+        if host.status.state == HostState::Up {
+            out.push('\n');
+            out.push_str(&render_host(host));
+        }
+    }
+
+    out
+}
+
+///Renders the section of the document for `host`.
+pub fn render_host(host: &Host) -> String {
+    let mut out = String::new();
+    let address = host.display_address().unwrap_or_default();
+    match host.primary_name() {
+        Some(name) => out.push_str(&format!("## {} ({})\n", address, name)),
+        None => out.push_str(&format!("## {}\n", address)),
+    }
+
+    let extra = host
+        .port_info
+        .extra_ports()
+        .map(|e| format!("{} {}", e.count, e.state))
+        .collect::<Vec<_>>();
+    if !extra.is_empty() {
+        out.push_str(&format!("\nNot shown: {} ports.\n", extra.join(", ")));
+    }
+
+    let ports = host.port_info.ports().collect::<Vec<_>>();
+    if !ports.is_empty() {
+        out.push_str("\n| Port | State | Service | Version |\n");
+        out.push_str("| --- | --- | --- | --- |\n");
+        for port in &ports {
+            let service = port.service_info.as_ref();
+            out.push_str(&format!(
+                "| {}/{} | {} | {} | {} |\n",
+                port.port_number,
+                port.protocol,
+                cell(&port.status.state.to_string()),
+                cell(service.map(|s| s.name.as_str()).unwrap_or_default()),
+                cell(&service.and_then(service_version).unwrap_or_default())
+            ));
+        }
+    }
+
+    for port in &ports {
+        for script in &port.scripts {
+            let label = format!("{}/{} {}", port.port_number, port.protocol, script.id);
+            render_script(&mut out, &label, script);
+        }
+    }
+    for script in host.scripts() {
+        render_script(&mut out, &script.id, script);
+    }
+
+    let os_matches = host.os_matches().collect::<Vec<_>>();
+    if !os_matches.is_empty() {
+        out.push_str("\n### OS detection\n\n");
+        for m in os_matches {
+            out.push_str(&format!("- {} ({}%)\n", m.name, m.accuracy));
+        }
+    }
+
+    out
+}
+
+fn render_script(out: &mut String, label: &str, script: &Script) {
+    let output = script.output.trim_matches('\n');
+
+    //The fence must be longer than any run of backticks in the output.
+    let mut fence = "```".to_string();
+    while output.contains(fence.as_str()) {
+        fence.push('`');
+    }
+
+    out.push_str(&format!(
+        "\n### {}\n\n{}\n{}\n{}\n",
+        label, fence, output, fence
+    ));
+}
+
+///Escapes `s` for use in a table cell.
+fn cell(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace('\n', " ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn escapes_table_cells_and_fences() {
+        let xml = r#"
+<nmaprun start="1588318812">
+<host><status state="up" reason="user-set" reason_ttl="0"/>
+<address addr="192.0.2.1" addrtype="ipv4"/>
+<ports>
+<port protocol="udp" portid="161"><state state="open|filtered" reason="no-response" reason_ttl="0"/><service name="snmp" method="table" conf="3"/><script id="x" output="```rust&#xa;```"/></port>
+</ports>
+</host>
+</nmaprun>
+"#;
+        let results = NmapResults::parse(xml).unwrap();
+        let host = results.hosts().next().unwrap();
+
+        assert_eq!(
+            render_host(host),
+            "## 192.0.2.1
+
+| Port | State | Service | Version |
+| --- | --- | --- | --- |
+| 161/udp | open\\|filtered | snmp |  |
+
+### 161/udp x
+
+````
+```rust
+```
+````
+"
+        );
+    }
+}
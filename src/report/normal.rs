@@ -0,0 +1,367 @@
+//!Renders scan results in the layout of Nmap's normal output (`-oN`).
+//!
+//!Only the per-host reports are rendered. The following parts of an `-oN`
+//!file are left out, as they are not per-host or are based on data this
+//!crate does not model:
+//!
+//!- the `# Nmap` comment lines at the start and end of the file
+//!- the closing "Service detection performed" or "OS and Service detection
+//!  performed" note
+//!- the `Network Distance` and `Service Info` lines
+//!- the `TRACEROUTE` section
+//!- the vendor after a MAC address, e.g. ` (VMware)`
+//!
+//!```
+//!# use std::path::PathBuf;
+//!# use std::fs;
+//!use nmap_xml_parser::NmapResults;
+//!use nmap_xml_parser::report::normal;
+//!# let mut nmap_xml_file = PathBuf::new();
+//!# nmap_xml_file.push(&std::env::var("CARGO_MANIFEST_DIR").unwrap());
+//!# nmap_xml_file.push("tests/scanme-version.xml");
+//!let content = fs::read_to_string(nmap_xml_file).unwrap();
+//!let results = NmapResults::parse(&content).unwrap();
+//!
+//!let report = normal::render(&results);
+//!assert!(report.starts_with("Nmap scan report for scanme.nmap.org (45.33.32.156)\n"));
+//!```
+use crate::host::{Address, Host, HostState, HostnameType, Script};
+use crate::os::OsMatch;
+use crate::report::service_version;
+use crate::NmapResults;
+
+///Renders the hosts that are up in `results`, each followed by a blank line.
+pub fn render(results: &NmapResults) -> String {
+    let mut out = String::new();
+    for host in results.hosts().filter(|h| h.status.state == HostState::Up) {
+        out.push_str(&render_host(host));
+        out.push('\n');
+    }
+    out
+}
+
+///Renders the report for `host`.
+pub fn render_host(host: &Host) -> String {
+    let mut out = String::new();
+    let target = target(host);
+
+    if host.status.state != HostState::Up {
+        out.push_str(&format!("Nmap scan report for {} [host down]\n", target));
+        return out;
+    }
+
+    out.push_str(&format!("Nmap scan report for {}\n", target));
+    match host.times.as_ref().filter(|t| t.srtt > 0) {
+        Some(t) => out.push_str(&format!(
+            "Host is up ({}s latency).\n",
+            format_g2(t.srtt as f64 / 1_000_000.0)
+        )),
+        None => out.push_str("Host is up.\n"),
+    }
+
+    if host.has_forward_reverse_mismatch() {
+        let ptr = host
+            .host_names()
+            .find(|h| h.source == HostnameType::Dns)
+            .map(|h| h.name.as_str())
+            .unwrap_or_default();
+        out.push_str(&format!(
+            "rDNS record for {}: {}\n",
+            host.display_address().unwrap_or_default(),
+            ptr
+        ));
+    }
+
+    render_ports(&mut out, host, &target);
+
+    for address in host.addresses() {
+        if let Address::MacAddr(mac) = address {
+            out.push_str(&format!("MAC Address: {}\n", mac));
+        }
+    }
+
+    render_os(&mut out, &host.os_matches().collect::<Vec<_>>());
+
+    if host.scripts().len() > 0 {
+        out.push_str("\nHost script results:\n");
+        for script in host.scripts() {
+            render_script(&mut out, script);
+        }
+    }
+
+    out
+}
+
+///Returns how Nmap refers to `host`, e.g. `scanme.nmap.org (45.33.32.156)`.
+fn target(host: &Host) -> String {
+    let address = host.display_address().unwrap_or_default();
+    match host.primary_name() {
+        Some(name) => format!("{} ({})", name, address),
+        None => address,
+    }
+}
+
+fn render_ports(out: &mut String, host: &Host, target: &str) {
+    let ports = host.port_info.ports().collect::<Vec<_>>();
+    let extra = host.port_info.extra_ports().collect::<Vec<_>>();
+
+    let not_shown = extra
+        .iter()
+        .map(|e| format!("{} {} ports", e.count, e.state))
+        .collect::<Vec<_>>();
+
+    if ports.is_empty() {
+        match extra.as_slice() {
+            [] => {}
+            [e] => out.push_str(&format!(
+                "All {} scanned ports on {} are {}\n",
+                e.count, target, e.state
+            )),
+            _ => {
+                let count = extra.iter().map(|e| e.count).sum::<u32>();
+                out.push_str(&format!(
+                    "All {} scanned ports on {} are in ignored states.\n",
+                    count, target
+                ));
+                out.push_str(&format!("Not shown: {}\n", not_shown.join(", ")));
+            }
+        }
+        return;
+    }
+
+    if extra.is_empty() {
+        out.push('\n');
+    } else {
+        out.push_str(&format!("Not shown: {}\n", not_shown.join(", ")));
+    }
+
+    let versions = ports
+        .iter()
+        .map(|p| p.service_info.as_ref().and_then(service_version))
+        .collect::<Vec<_>>();
+    let with_version = versions.iter().any(|v| v.is_some());
+
+    let mut rows = vec![vec![
+        "PORT".to_string(),
+        "STATE".to_string(),
+        "SERVICE".to_string(),
+    ]];
+    if with_version {
+        rows[0].push("VERSION".to_string());
+    }
+    for (port, version) in ports.iter().zip(versions) {
+        let mut row = vec![
+            format!("{}/{}", port.port_number, port.protocol),
+            port.status.state.to_string(),
+            port.service_info
+                .as_ref()
+                .map(|s| s.name.clone())
+                .unwrap_or_default(),
+        ];
+        if with_version {
+            row.push(version.unwrap_or_default());
+        }
+        rows.push(row);
+    }
+
+    //Every column but the last is padded to its widest cell.
+    let columns = rows[0].len();
+    let widths = (0..columns - 1)
+        .map(|i| rows.iter().map(|r| r[i].len()).max().unwrap_or(0))
+        .collect::<Vec<_>>();
+    let format_row = |row: &[String]| {
+        let mut line = String::new();
+        for (i, cell) in row.iter().enumerate() {
+            match widths.get(i) {
+                Some(w) => line.push_str(&format!("{:<w$} ", cell, w = w)),
+                None => line.push_str(cell),
+            }
+        }
+        format!("{}\n", line.trim_end())
+    };
+
+    out.push_str(&format_row(&rows[0]));
+    for (port, row) in ports.iter().zip(&rows[1..]) {
+        out.push_str(&format_row(row));
+        for script in &port.scripts {
+            render_script(out, script);
+        }
+    }
+}
+
+///Writes script output the way Nmap does: every line is prefixed with `|`
+///and the last line with `|_`.
+fn render_script(out: &mut String, script: &Script) {
+    let output = format!("{}: {}", script.id, script.output.trim_end_matches('\n'));
+    let lines = output.split('\n').collect::<Vec<_>>();
+
+    for (i, line) in lines.iter().enumerate() {
+        let prefix = if i + 1 == lines.len() { "|_" } else { "| " };
+        out.push_str(prefix);
+        out.push_str(line);
+        out.push('\n');
+    }
+}
+
+fn render_os(out: &mut String, matches: &[&OsMatch]) {
+    if matches.is_empty() {
+        return;
+    }
+
+    let exact = matches
+        .iter()
+        .filter(|m| m.accuracy == 100)
+        .collect::<Vec<_>>();
+
+    //Without an exact match, Nmap describes the classes of the guesses
+    //that are within 10 points of the best one.
+    let classes = if exact.is_empty() {
+        let best = matches.iter().map(|m| m.accuracy).max().unwrap_or(0);
+        matches
+            .iter()
+            .flat_map(|m| &m.classes)
+            .filter(|c| c.accuracy + 10 >= best)
+            .collect::<Vec<_>>()
+    } else {
+        exact.iter().flat_map(|m| &m.classes).collect::<Vec<_>>()
+    };
+
+    let mut device_types = Vec::new();
+    for t in classes.iter().filter_map(|c| c.device_type.as_deref()) {
+        if !device_types.contains(&t) {
+            device_types.push(t);
+        }
+    }
+    if !device_types.is_empty() {
+        out.push_str(&format!("Device type: {}\n", device_types.join("|")));
+    }
+
+    //Generations are grouped by vendor and family, e.g. "Linux 3.X|4.X",
+    //along with the best accuracy in the group.
+    let mut running: Vec<(String, Vec<&str>, u8)> = Vec::new();
+    for c in &classes {
+        let name = if c.vendor == c.family {
+            c.vendor.clone()
+        } else {
+            format!("{} {}", c.vendor, c.family)
+        };
+        let i = match running.iter().position(|(n, _, _)| *n == name) {
+            Some(i) => i,
+            None => {
+                running.push((name, Vec::new(), 0));
+                running.len() - 1
+            }
+        };
+        if let Some(generation) = c.generation.as_deref() {
+            if !running[i].1.contains(&generation) {
+                running[i].1.push(generation);
+            }
+        }
+        running[i].2 = running[i].2.max(c.accuracy);
+    }
+    let running = running
+        .iter()
+        .map(|(name, gens, accuracy)| {
+            let group = match gens.as_slice() {
+                [] => name.clone(),
+                gens => format!("{} {}", name, gens.join("|")),
+            };
+            if exact.is_empty() {
+                format!("{} ({}%)", group, accuracy)
+            } else {
+                group
+            }
+        })
+        .collect::<Vec<_>>();
+    if exact.is_empty() {
+        out.push_str(&format!(
+            "Running (JUST GUESSING): {}\n",
+            running.join(", ")
+        ));
+    } else {
+        out.push_str(&format!("Running: {}\n", running.join(", ")));
+    }
+
+    let mut cpes = Vec::new();
+    for class in &classes {
+        let unparsed = class.unparsed_cpe.iter().cloned();
+        for cpe in class.cpe.iter().map(|c| c.to_string()).chain(unparsed) {
+            if !cpes.contains(&cpe) {
+                cpes.push(cpe);
+            }
+        }
+    }
+    if !cpes.is_empty() {
+        out.push_str(&format!("OS CPE: {}\n", cpes.join(" ")));
+    }
+
+    if exact.is_empty() {
+        let guesses = matches
+            .iter()
+            .map(|m| format!("{} ({}%)", m.name, m.accuracy))
+            .collect::<Vec<_>>();
+        out.push_str(&format!("Aggressive OS guesses: {}\n", guesses.join(", ")));
+        out.push_str("No exact OS matches for host (test conditions non-ideal).\n");
+    } else {
+        let names = exact.iter().map(|m| m.name.as_str()).collect::<Vec<_>>();
+        out.push_str(&format!("OS details: {}\n", names.join(", ")));
+    }
+}
+
+///Formats `v` with two significant digits, keeping trailing zeros, as Nmap
+///does for latencies.
+fn format_g2(v: f64) -> String {
+    if v <= 0.0 {
+        return "0".to_string();
+    }
+
+    let exponent = v.log10().floor() as i32;
+    let decimals = (1 - exponent).max(0) as usize;
+    format!("{:.*}", decimals, v)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn latency_format() {
+        assert_eq!(format_g2(0.153588), "0.15");
+        assert_eq!(format_g2(0.000312), "0.00031");
+        assert_eq!(format_g2(0.1), "0.10");
+        assert_eq!(format_g2(0.002), "0.0020");
+        assert_eq!(format_g2(1.25), "1.2");
+    }
+
+    #[test]
+    fn exact_os_match() {
+        let xml = r#"
+<nmaprun start="1588318812">
+<host><status state="up" reason="echo-reply" reason_ttl="53"/>
+<address addr="192.0.2.1" addrtype="ipv4"/>
+<ports><extraports state="closed" count="1000"/></ports>
+<os>
+<osmatch name="Linux 3.2 - 4.9" accuracy="100" line="1">
+<osclass type="general purpose" vendor="Linux" osfamily="Linux" osgen="3.X" accuracy="100"><cpe>cpe:/o:linux:linux_kernel:3</cpe></osclass>
+<osclass type="general purpose" vendor="Linux" osfamily="Linux" osgen="4.X" accuracy="100"><cpe>cpe:/o:linux:linux_kernel:4</cpe></osclass>
+</osmatch>
+</os>
+</host>
+</nmaprun>
+"#;
+        let results = NmapResults::parse(xml).unwrap();
+
+        assert_eq!(
+            render(&results),
+            "Nmap scan report for 192.0.2.1
+Host is up.
+All 1000 scanned ports on 192.0.2.1 are closed
+Device type: general purpose
+Running: Linux 3.X|4.X
+OS CPE: cpe:/o:linux:linux_kernel:3 cpe:/o:linux:linux_kernel:4
+OS details: Linux 3.2 - 4.9
+
+"
+        );
+    }
+}
@@ -0,0 +1,240 @@
+//!Target lists, port specifications and command lines to rescan hosts.
+//!
+//!```
+//!# use std::path::PathBuf;
+//!# use std::fs;
+//!use nmap_xml_parser::NmapResults;
+//!use nmap_xml_parser::port::PortState;
+//!use nmap_xml_parser::rescan;
+//!# let mut nmap_xml_file = PathBuf::new();
+//!# nmap_xml_file.push(&std::env::var("CARGO_MANIFEST_DIR").unwrap());
+//!# nmap_xml_file.push("tests/scanme-version.xml");
+//!let content = fs::read_to_string(nmap_xml_file).unwrap();
+//!let results = NmapResults::parse(&content).unwrap();
+//!
+//!let open = results
+//!    .iter_ports()
+//!    .filter(|(_, p)| p.status.state == PortState::Open)
+//!    .collect::<Vec<_>>();
+//!let targets = rescan::ip_targets(open.iter().map(|(h, _)| *h));
+//!let ports = rescan::port_spec(open.iter().map(|(_, p)| *p));
+//!
+//!let original = results.scan_options().unwrap();
+//!let command = rescan::command(&original, targets, ports);
+//!assert_eq!(
+//!    command.to_string(),
+//!    "nmap -A -p T:22,80,9929,31337 45.33.32.156"
+//!);
+//!```
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::args::{ScanOption, ScanOptions};
+use crate::host::Host;
+use crate::port::{Port, PortProtocol};
+use crate::portset::PortSet;
+
+///Options that select targets, which a rescan replaces.
+const TARGET_OPTIONS: &[&str] = &["-iL", "-iR", "--resume"];
+
+///Options that select ports, which a rescan replaces if it is given ports.
+const PORT_OPTIONS: &[&str] = &["-p", "-F", "--top-ports", "--port-ratio"];
+
+///Output options, which a rescan drops so that it does not overwrite the
+///output of the original scan.
+const OUTPUT_OPTIONS: &[&str] = &["-oN", "-oX", "-oS", "-oG", "-oA", "-oM", "-oH"];
+
+///Returns the IP addresses of `hosts`, without duplicates.
+pub fn ip_targets<'a, I>(hosts: I) -> Vec<String>
+where
+    I: IntoIterator<Item = &'a Host>,
+{
+    let mut r = Vec::new();
+    for host in hosts {
+        for address in host.addresses() {
+            if address.ip().is_some() {
+                push_unique(&mut r, host.address_value(address));
+            }
+        }
+    }
+    r
+}
+
+///Returns the names of `hosts` as returned by
+///[`Host::primary_name()`](../host/struct.Host.html#method.primary_name),
+///without duplicates. Hosts without a name are given by their first IP
+///address.
+pub fn name_targets<'a, I>(hosts: I) -> Vec<String>
+where
+    I: IntoIterator<Item = &'a Host>,
+{
+    let mut r = Vec::new();
+    for host in hosts {
+        let target = host.primary_name().map(String::from).or_else(|| {
+            host.addresses()
+                .find(|a| a.ip().is_some())
+                .map(|a| host.address_value(a))
+        });
+        if let Some(target) = target {
+            push_unique(&mut r, target);
+        }
+    }
+    r
+}
+
+///Writes `targets` to `writer` in the format read by Nmap's `-iL` option,
+///one target per line.
+pub fn write_target_list<W, S>(targets: &[S], mut writer: W) -> io::Result<()>
+where
+    W: Write,
+    S: AsRef<str>,
+{
+    for target in targets {
+        writeln!(writer, "{}", target.as_ref())?;
+    }
+    Ok(())
+}
+
+///Returns a port specification for `-p` that covers `ports`, e.g.
+///`T:22,80,U:53`, or `None` if `ports` is empty.
+///
+///Ports are sorted and consecutive ports are merged into ranges. Note that
+///Nmap only scans the UDP, SCTP and IP protocol ports of the specification
+///if the matching scan type (e.g. `-sU`) is also given.
+pub fn port_spec<'a, I>(ports: I) -> Option<String>
+where
+    I: IntoIterator<Item = &'a Port>,
+{
+    let mut by_protocol: BTreeMap<&PortProtocol, PortSet> = BTreeMap::new();
+    for port in ports {
+        by_protocol
+            .entry(&port.protocol)
+            .or_default()
+            .insert(port.port_number);
+    }
+
+    let spec = by_protocol
+        .into_iter()
+        .map(|(protocol, set)| format!("{}:{}", protocol_prefix(protocol), set))
+        .collect::<Vec<_>>();
+
+    if spec.is_empty() {
+        None
+    } else {
+        Some(spec.join(","))
+    }
+}
+
+///Returns the command line of `original` changed to scan `targets`, and
+///`ports` if given.
+///
+///Options of `original` that select targets are removed, as are output
+///options so that the rescan does not overwrite the original output.
+pub fn command(original: &ScanOptions, targets: Vec<String>, ports: Option<String>) -> ScanOptions {
+    let mut r = rescan_options(original, ports);
+    r.targets = targets;
+    r
+}
+
+///Like [`command()`](fn.command.html), but reads the targets from the file
+///at `path`, as written by
+///[`write_target_list()`](fn.write_target_list.html).
+pub fn command_with_target_list<P: AsRef<Path>>(
+    original: &ScanOptions,
+    path: P,
+    ports: Option<String>,
+) -> ScanOptions {
+    let mut r = rescan_options(original, ports);
+    let path = path.as_ref().to_string_lossy();
+    r.options.push(ScanOption::new("-iL", Some(&path)));
+    r
+}
+
+fn rescan_options(original: &ScanOptions, ports: Option<String>) -> ScanOptions {
+    let mut r = original.clone();
+    r.targets.clear();
+    r.remove(TARGET_OPTIONS);
+    r.remove(OUTPUT_OPTIONS);
+    if let Some(ports) = ports {
+        r.remove(PORT_OPTIONS);
+        r.options.push(ScanOption::new("-p", Some(&ports)));
+    }
+    r
+}
+
+///Returns the prefix of `protocol` in a port specification.
+fn protocol_prefix(protocol: &PortProtocol) -> &'static str {
+    match protocol {
+        PortProtocol::Tcp => "T",
+        PortProtocol::Udp => "U",
+        PortProtocol::Sctp => "S",
+        PortProtocol::Ip => "P",
+    }
+}
+
+fn push_unique(targets: &mut Vec<String>, target: String) {
+    if !targets.contains(&target) {
+        targets.push(target);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::NmapResults;
+
+    const XML: &str = r#"
+<nmaprun args="nmap -sS -sU -p- -T4 -oA first -iL all.txt" start="1588318812">
+<host><status state="up" reason="user-set" reason_ttl="0"/>
+<address addr="192.0.2.1" addrtype="ipv4"/>
+<hostnames><hostname name="a.example" type="user"/></hostnames>
+<ports>
+<port protocol="udp" portid="53"><state state="open" reason="udp-response" reason_ttl="64"/></port>
+<port protocol="tcp" portid="80"><state state="open" reason="syn-ack" reason_ttl="64"/></port>
+<port protocol="tcp" portid="22"><state state="open" reason="syn-ack" reason_ttl="64"/></port>
+<port protocol="tcp" portid="23"><state state="open" reason="syn-ack" reason_ttl="64"/></port>
+<port protocol="tcp" portid="24"><state state="open" reason="syn-ack" reason_ttl="64"/></port>
+</ports>
+</host>
+<host><status state="up" reason="user-set" reason_ttl="0"/>
+<address addr="192.0.2.2" addrtype="ipv4"/>
+<ports>
+<port protocol="tcp" portid="80"><state state="open" reason="syn-ack" reason_ttl="64"/></port>
+</ports>
+</host>
+</nmaprun>
+"#;
+
+    #[test]
+    fn targets_and_ports() {
+        let results = NmapResults::parse(XML).unwrap();
+
+        assert_eq!(ip_targets(results.hosts()), vec!["192.0.2.1", "192.0.2.2"]);
+        assert_eq!(
+            name_targets(results.hosts()),
+            vec!["a.example", "192.0.2.2"]
+        );
+        assert_eq!(
+            port_spec(results.iter_ports().map(|(_, p)| p)).as_deref(),
+            Some("T:22-24,80,U:53")
+        );
+        assert_eq!(port_spec(Vec::new()), None);
+
+        let mut list = Vec::new();
+        write_target_list(&ip_targets(results.hosts()), &mut list).unwrap();
+        assert_eq!(list, b"192.0.2.1\n192.0.2.2\n");
+    }
+
+    #[test]
+    fn commands() {
+        let results = NmapResults::parse(XML).unwrap();
+        let original = results.scan_options().unwrap();
+
+        let rescan = command(&original, vec!["192.0.2.2".to_string()], None);
+        assert_eq!(rescan.to_string(), "nmap -sS -sU -p- -T4 192.0.2.2");
+
+        let rescan = command_with_target_list(&original, "open.txt", Some("T:80".to_string()));
+        assert_eq!(rescan.to_string(), "nmap -sS -sU -T4 -p T:80 -iL open.txt");
+    }
+}
@@ -0,0 +1,344 @@
+//!Typed decoders for the output of common NSE scripts.
+//!
+//!Each supported script has a struct with a `decode()` function that takes a
+//![`Script`](../host/struct.Script.html) and returns `None` if the script
+//!has a different ID or its output cannot be understood. A
+//![`Decoders`](struct.Decoders.html) registry dispatches on
+//![`Script::id`](../host/struct.Script.html#structfield.id) and can be
+//!extended with user-defined [`ScriptDecoder`](trait.ScriptDecoder.html)s:
+//!
+//!```
+//!# use std::path::PathBuf;
+//!# use std::fs;
+//!use nmap_xml_parser::NmapResults;
+//!use nmap_xml_parser::scripts::{Decoded, Decoders};
+//!# let mut nmap_xml_file = PathBuf::new();
+//!# nmap_xml_file.push(&std::env::var("CARGO_MANIFEST_DIR").unwrap());
+//!# nmap_xml_file.push("tests/scanme-version.xml");
+//!let content = fs::read_to_string(nmap_xml_file).unwrap();
+//!let results = NmapResults::parse(&content).unwrap();
+//!let decoders = Decoders::new();
+//!
+//!for (_, port) in results.iter_ports() {
+//!    for script in &port.scripts {
+//!        if let Some(Decoded::HttpTitle(t)) = decoders.decode(script) {
+//!            println!("{}: {:?}", port.port_number, t.title);
+//!        }
+//!    }
+//!}
+//!```
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::cpe::Cpe;
+use crate::host::{Script, ScriptElement, ScriptValue};
+
+///Decodes the output of one NSE script.
+pub trait ScriptDecoder: Send + Sync {
+    ///Returns the ID of the script this decoder handles.
+    fn script_id(&self) -> &str;
+
+    ///Decodes `script`, returning `None` if its output cannot be understood.
+    fn decode(&self, script: &Script) -> Option<Decoded>;
+}
+
+///The result of decoding a script.
+#[derive(Debug)]
+pub enum Decoded {
+    SslCert(SslCert),
+    SshHostKeys(Vec<SshHostKey>),
+    HttpTitle(HttpTitle),
+    SmbOsDiscovery(Box<SmbOsDiscovery>),
+    ///Output of a user-defined decoder.
+    Custom(Box<dyn Any + Send + Sync>),
+}
+
+///A registry of script decoders keyed on script ID.
+pub struct Decoders {
+    decoders: HashMap<String, Box<dyn ScriptDecoder>>,
+}
+
+impl Decoders {
+    ///Returns a registry containing the decoders provided by this crate.
+    pub fn new() -> Self {
+        let mut r = Decoders::empty();
+        r.register(Builtin {
+            id: "ssl-cert",
+            decode: |s| SslCert::decode(s).map(Decoded::SslCert),
+        });
+        r.register(Builtin {
+            id: "ssh-hostkey",
+            decode: |s| SshHostKey::decode_all(s).map(Decoded::SshHostKeys),
+        });
+        r.register(Builtin {
+            id: "http-title",
+            decode: |s| HttpTitle::decode(s).map(Decoded::HttpTitle),
+        });
+        r.register(Builtin {
+            id: "smb-os-discovery",
+            decode: |s| SmbOsDiscovery::decode(s).map(|d| Decoded::SmbOsDiscovery(Box::new(d))),
+        });
+        r
+    }
+
+    ///Returns a registry with no decoders.
+    pub fn empty() -> Self {
+        Decoders {
+            decoders: HashMap::new(),
+        }
+    }
+
+    ///Adds `decoder`, replacing any existing decoder for the same script ID.
+    pub fn register<D: ScriptDecoder + 'static>(&mut self, decoder: D) {
+        self.decoders
+            .insert(decoder.script_id().to_string(), Box::new(decoder));
+    }
+
+    ///Decodes `script` with the decoder registered for its ID, if any.
+    pub fn decode(&self, script: &Script) -> Option<Decoded> {
+        self.decoders.get(&script.id).and_then(|d| d.decode(script))
+    }
+}
+
+impl Default for Decoders {
+    fn default() -> Self {
+        Decoders::new()
+    }
+}
+
+impl fmt::Debug for Decoders {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut ids = self.decoders.keys().collect::<Vec<_>>();
+        ids.sort();
+        f.debug_struct("Decoders").field("decoders", &ids).finish()
+    }
+}
+
+struct Builtin {
+    id: &'static str,
+    decode: fn(&Script) -> Option<Decoded>,
+}
+
+impl ScriptDecoder for Builtin {
+    fn script_id(&self) -> &str {
+        self.id
+    }
+
+    fn decode(&self, script: &Script) -> Option<Decoded> {
+        (self.decode)(script)
+    }
+}
+
+///A distinguished name as a list of attribute names and values, e.g.
+///`("commonName", "scanme.nmap.org")`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DistinguishedName(pub Vec<(String, String)>);
+
+impl DistinguishedName {
+    ///Returns the value of the first attribute called `name`.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn common_name(&self) -> Option<&str> {
+        self.get("commonName")
+    }
+
+    fn from_table(elements: &[ScriptElement]) -> Self {
+        DistinguishedName(
+            elements
+                .iter()
+                .filter_map(|e| Some((e.key.clone()?, e.value.as_str()?.to_string())))
+                .collect(),
+        )
+    }
+}
+
+///A TLS certificate from the `ssl-cert` script.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SslCert {
+    pub subject: DistinguishedName,
+    pub issuer: DistinguishedName,
+    ///DNS names, IP addresses and other entries of the subject alternative
+    ///name extension, as printed by Nmap (e.g. `DNS:scanme.nmap.org`).
+    pub subject_alt_names: Vec<String>,
+    ///Start of the validity period, e.g. `2020-01-06T14:30:12`.
+    pub not_before: Option<String>,
+    ///End of the validity period, e.g. `2022-01-05T14:30:12`.
+    pub not_after: Option<String>,
+    pub key_type: Option<String>,
+    pub key_bits: Option<u32>,
+    pub signature_algorithm: Option<String>,
+    pub md5: Option<String>,
+    pub sha1: Option<String>,
+    pub pem: Option<String>,
+}
+
+impl SslCert {
+    ///Decodes the output of an `ssl-cert` script.
+    pub fn decode(script: &Script) -> Option<Self> {
+        if script.id != "ssl-cert" {
+            return None;
+        }
+
+        let table = |key| script.get(key).and_then(|v| v.as_table());
+        let text = |v: Option<&ScriptValue>| v.and_then(|v| v.as_str()).map(|s| s.to_string());
+
+        let subject_alt_names = table("extensions")
+            .unwrap_or(&[])
+            .iter()
+            .filter(|e| {
+                e.value.get("name").and_then(|v| v.as_str())
+                    == Some("X509v3 Subject Alternative Name")
+            })
+            .filter_map(|e| e.value.get("value").and_then(|v| v.as_str()))
+            .flat_map(|v| v.split(", "))
+            .map(|s| s.trim().to_string())
+            .collect();
+
+        let pubkey = script.get("pubkey");
+        let validity = script.get("validity");
+
+        Some(SslCert {
+            subject: DistinguishedName::from_table(table("subject")?),
+            issuer: DistinguishedName::from_table(table("issuer").unwrap_or(&[])),
+            subject_alt_names,
+            not_before: text(validity.and_then(|v| v.get("notBefore"))),
+            not_after: text(validity.and_then(|v| v.get("notAfter"))),
+            key_type: text(pubkey.and_then(|v| v.get("type"))),
+            key_bits: text(pubkey.and_then(|v| v.get("bits"))).and_then(|s| s.parse().ok()),
+            signature_algorithm: text(script.get("sig_algo")),
+            md5: text(script.get("md5")),
+            sha1: text(script.get("sha1")),
+            pem: text(script.get("pem")),
+        })
+    }
+}
+
+///An SSH host key from the `ssh-hostkey` script.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SshHostKey {
+    ///Key type, e.g. `ssh-rsa` or `ssh-ed25519`.
+    pub key_type: String,
+    pub bits: Option<u32>,
+    ///MD5 fingerprint as lowercase hex without separators.
+    pub fingerprint: String,
+    ///Base64-encoded public key.
+    pub key: Option<String>,
+}
+
+impl SshHostKey {
+    ///Decodes all host keys in the output of an `ssh-hostkey` script.
+    pub fn decode_all(script: &Script) -> Option<Vec<Self>> {
+        if script.id != "ssh-hostkey" {
+            return None;
+        }
+
+        let text =
+            |t: &ScriptValue, key| t.get(key).and_then(|v| v.as_str()).map(|s| s.to_string());
+
+        let keys = script
+            .elements
+            .iter()
+            .filter_map(|e| {
+                let t = &e.value;
+                Some(SshHostKey {
+                    key_type: text(t, "type")?,
+                    bits: text(t, "bits").and_then(|s| s.parse().ok()),
+                    fingerprint: text(t, "fingerprint")?,
+                    key: text(t, "key"),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        if keys.is_empty() {
+            None
+        } else {
+            Some(keys)
+        }
+    }
+}
+
+///The page title from the `http-title` script.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HttpTitle {
+    ///Title of the page. `None` if the page has no title or a redirect was
+    ///not followed.
+    pub title: Option<String>,
+    ///Target of a redirect that Nmap did not follow.
+    pub redirect_url: Option<String>,
+}
+
+impl HttpTitle {
+    ///Decodes the output of an `http-title` script.
+    pub fn decode(script: &Script) -> Option<Self> {
+        if script.id != "http-title" {
+            return None;
+        }
+
+        let text = |key| {
+            script
+                .get(key)
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+        };
+        Some(HttpTitle {
+            title: text("title"),
+            redirect_url: text("redirect_url"),
+        })
+    }
+}
+
+///Operating system and domain details from the `smb-os-discovery` script.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SmbOsDiscovery {
+    pub os: Option<String>,
+    pub lan_manager: Option<String>,
+    pub netbios_computer_name: Option<String>,
+    pub workgroup: Option<String>,
+    ///NetBIOS domain name, e.g. `CORP`.
+    pub domain: Option<String>,
+    ///DNS domain name, e.g. `corp.example`.
+    pub domain_dns: Option<String>,
+    ///DNS name of the Active Directory forest.
+    pub forest: Option<String>,
+    pub fqdn: Option<String>,
+    ///System time reported by the server.
+    pub date: Option<String>,
+    ///CPE name of the operating system. `None` if the script reported a
+    ///value that is not a valid CPE name.
+    pub cpe: Option<Cpe>,
+}
+
+impl SmbOsDiscovery {
+    ///Decodes the output of an `smb-os-discovery` script.
+    pub fn decode(script: &Script) -> Option<Self> {
+        if script.id != "smb-os-discovery" {
+            return None;
+        }
+
+        //NetBIOS names are printed with a trailing escaped NUL.
+        let text = |key| {
+            script
+                .get(key)
+                .and_then(|v| v.as_str())
+                .map(|s| s.trim_end_matches("\\x00").to_string())
+        };
+        Some(SmbOsDiscovery {
+            os: text("os"),
+            lan_manager: text("lanmanager"),
+            netbios_computer_name: text("server"),
+            workgroup: text("workgroup"),
+            domain: text("domain"),
+            domain_dns: text("domain_dns"),
+            forest: text("forest_dns"),
+            fqdn: text("fqdn"),
+            date: text("date"),
+            cpe: text("cpe").and_then(|s| s.parse().ok()),
+        })
+    }
+}
@@ -0,0 +1,243 @@
+//!Summary statistics for a scan.
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::host::{Host, HostState};
+use crate::port::{PortProtocol, PortState};
+use crate::NmapResults;
+
+///Counts of hosts, ports, services and products in a scan, as returned by
+///[`NmapResults::stats()`](../struct.NmapResults.html#method.stats).
+///
+///Service and product counts only include open ports, as services of other
+///ports are guessed from the port number.
+#[derive(Clone, Debug)]
+pub struct ScanStats<'a> {
+    pub hosts_by_state: HashMap<HostState, usize>,
+    ///Number of ports in each state, including the ports Nmap does not list
+    ///individually but summarizes in `extraports`.
+    pub ports_by_state: HashMap<PortState, usize>,
+    ///Number of ports of each protocol, including `extraports`. Before
+    ///Nmap 7.90, `extraports` does not say which protocol it describes, so
+    ///those ports are only counted if a single protocol was scanned.
+    pub ports_by_protocol: HashMap<PortProtocol, usize>,
+    ///Number of open ports running each service, keyed on service name.
+    pub services: HashMap<&'a str, usize>,
+    ///Number of open ports running each product, keyed on product name.
+    pub products: HashMap<&'a str, usize>,
+    ///Number of open ports on each host, in scan order.
+    pub open_ports: Vec<(&'a Host, usize)>,
+    ///Duration of the scan in seconds, if it finished.
+    pub duration: Option<i64>,
+}
+
+impl<'a> ScanStats<'a> {
+    pub(crate) fn new(results: &'a NmapResults) -> Self {
+        let mut stats = ScanStats {
+            hosts_by_state: HashMap::new(),
+            ports_by_state: HashMap::new(),
+            ports_by_protocol: HashMap::new(),
+            services: HashMap::new(),
+            products: HashMap::new(),
+            open_ports: Vec::new(),
+            duration: results
+                .scan_end_time
+                .map(|end| end - results.scan_start_time),
+        };
+
+        let mut protocols = results.scan_info().map(|i| &i.protocol).collect::<Vec<_>>();
+        protocols.sort();
+        protocols.dedup();
+        let single_protocol = match protocols.as_slice() {
+            [protocol] => Some(*protocol),
+            _ => None,
+        };
+
+        for host in results.hosts() {
+            *stats
+                .hosts_by_state
+                .entry(host.status.state.clone())
+                .or_insert(0) += 1;
+
+            let mut open = 0;
+            for port in host.port_info.ports() {
+                *stats
+                    .ports_by_state
+                    .entry(port.status.state.clone())
+                    .or_insert(0) += 1;
+                *stats
+                    .ports_by_protocol
+                    .entry(port.protocol.clone())
+                    .or_insert(0) += 1;
+
+                if port.status.state != PortState::Open {
+                    continue;
+                }
+                open += 1;
+
+                if let Some(service) = &port.service_info {
+                    *stats.services.entry(&service.name).or_insert(0) += 1;
+                    if let Some(product) = &service.product {
+                        *stats.products.entry(product).or_insert(0) += 1;
+                    }
+                }
+            }
+            for extra in host.port_info.extra_ports() {
+                let count = extra.count as usize;
+                *stats.ports_by_state.entry(extra.state.clone()).or_insert(0) += count;
+
+                let mut counted = 0;
+                for reason in &extra.reasons {
+                    if let Some(protocol) = &reason.protocol {
+                        *stats.ports_by_protocol.entry(protocol.clone()).or_insert(0) +=
+                            reason.count as usize;
+                        counted += reason.count as usize;
+                    }
+                }
+                if let (Some(protocol), true) = (single_protocol, counted < count) {
+                    *stats.ports_by_protocol.entry(protocol.clone()).or_insert(0) +=
+                        count - counted;
+                }
+            }
+            stats.open_ports.push((host, open));
+        }
+
+        stats
+    }
+
+    ///Returns the `n` most common services on open ports with their counts,
+    ///most common first.
+    pub fn top_services(&self, n: usize) -> Vec<(&'a str, usize)> {
+        top(&self.services, n)
+    }
+
+    ///Returns the `n` most common products on open ports with their counts,
+    ///most common first.
+    pub fn top_products(&self, n: usize) -> Vec<(&'a str, usize)> {
+        top(&self.products, n)
+    }
+
+    ///Returns the `n` hosts with the most open ports with their counts,
+    ///most open ports first. Hosts without open ports are not included.
+    pub fn hosts_with_most_open_ports(&self, n: usize) -> Vec<(&'a Host, usize)> {
+        let mut hosts = self
+            .open_ports
+            .iter()
+            .filter(|(_, count)| *count > 0)
+            .cloned()
+            .collect::<Vec<_>>();
+        //Stable sort, so ties stay in scan order.
+        hosts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        hosts.truncate(n);
+        hosts
+    }
+}
+
+///Returns the `n` largest entries of `counts`, breaking ties by key.
+fn top<K: Copy + Ord + Hash>(counts: &HashMap<K, usize>, n: usize) -> Vec<(K, usize)> {
+    let mut entries = counts.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>();
+    entries.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    entries.truncate(n);
+    entries
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::host::Address;
+
+    const SCAN: &str = r#"
+<nmaprun start="1588318812">
+<host><status state="up" reason="echo-reply" reason_ttl="53"/>
+<address addr="192.0.2.1" addrtype="ipv4"/>
+<ports>
+<port protocol="tcp" portid="22"><state state="open" reason="syn-ack" reason_ttl="53"/><service name="ssh" product="OpenSSH" method="probed" conf="10"/></port>
+<port protocol="tcp" portid="80"><state state="open" reason="syn-ack" reason_ttl="53"/><service name="http" product="nginx" method="probed" conf="10"/></port>
+<port protocol="udp" portid="53"><state state="open" reason="udp-response" reason_ttl="53"/><service name="domain" method="table" conf="3"/></port>
+</ports>
+</host>
+<host><status state="up" reason="echo-reply" reason_ttl="53"/>
+<address addr="192.0.2.2" addrtype="ipv4"/>
+<ports>
+<port protocol="tcp" portid="22"><state state="open" reason="syn-ack" reason_ttl="53"/><service name="ssh" product="OpenSSH" method="probed" conf="10"/></port>
+<port protocol="tcp" portid="443"><state state="closed" reason="reset" reason_ttl="53"/><service name="https" method="table" conf="3"/></port>
+</ports>
+</host>
+<host><status state="down" reason="no-response" reason_ttl="0"/>
+<address addr="192.0.2.3" addrtype="ipv4"/>
+</host>
+<runstats><finished time="1588318912"/></runstats>
+</nmaprun>
+"#;
+
+    #[test]
+    fn counts() {
+        let results = NmapResults::parse(SCAN).unwrap();
+        let stats = results.stats();
+
+        assert_eq!(stats.hosts_by_state[&HostState::Up], 2);
+        assert_eq!(stats.hosts_by_state[&HostState::Down], 1);
+        assert_eq!(stats.ports_by_state[&PortState::Open], 4);
+        assert_eq!(stats.ports_by_state[&PortState::Closed], 1);
+        assert_eq!(stats.ports_by_protocol[&PortProtocol::Tcp], 4);
+        assert_eq!(stats.ports_by_protocol[&PortProtocol::Udp], 1);
+        assert_eq!(stats.duration, Some(100));
+    }
+
+    #[test]
+    fn extra_ports() {
+        let xml = r#"<nmaprun start="1588318812">
+<scaninfo type="syn" protocol="tcp" numservices="1000" services="1-1000"/>
+<host><status state="up" reason="echo-reply" reason_ttl="53"/>
+<address addr="192.0.2.1" addrtype="ipv4"/>
+<ports>
+<extraports state="closed" count="990"><extrareasons reason="resets" count="990"/></extraports>
+<extraports state="filtered" count="8"><extrareasons reason="no-responses" count="8"/></extraports>
+<port protocol="tcp" portid="22"><state state="open" reason="syn-ack" reason_ttl="53"/></port>
+<port protocol="tcp" portid="80"><state state="closed" reason="reset" reason_ttl="53"/></port>
+</ports>
+</host>
+</nmaprun>"#;
+        let results = NmapResults::parse(xml).unwrap();
+        let stats = results.stats();
+
+        assert_eq!(stats.ports_by_state[&PortState::Open], 1);
+        assert_eq!(stats.ports_by_state[&PortState::Closed], 991);
+        assert_eq!(stats.ports_by_state[&PortState::Filtered], 8);
+        assert_eq!(stats.ports_by_protocol[&PortProtocol::Tcp], 1000);
+        assert_eq!(stats.hosts_with_most_open_ports(1)[0].1, 1);
+
+        //With several protocols and no `proto` on the reasons, the
+        //protocol of the unlisted ports is unknown.
+        let xml = xml.replace(
+            "<host>",
+            r#"<scaninfo type="udp" protocol="udp" numservices="1" services="53"/><host>"#,
+        );
+        let results = NmapResults::parse(&xml).unwrap();
+        let stats = results.stats();
+        assert_eq!(stats.ports_by_state[&PortState::Closed], 991);
+        assert_eq!(stats.ports_by_protocol[&PortProtocol::Tcp], 2);
+    }
+
+    #[test]
+    fn top_n() {
+        let results = NmapResults::parse(SCAN).unwrap();
+        let stats = results.stats();
+
+        assert_eq!(stats.top_services(2), vec![("ssh", 2), ("domain", 1)]);
+        assert_eq!(stats.top_products(5), vec![("OpenSSH", 2), ("nginx", 1)]);
+
+        let hosts = stats
+            .hosts_with_most_open_ports(5)
+            .iter()
+            .map(|(h, n)| (h.addresses().next().unwrap().clone(), *n))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            hosts,
+            vec![
+                (Address::IpAddr("192.0.2.1".parse().unwrap()), 3),
+                (Address::IpAddr("192.0.2.2".parse().unwrap()), 1),
+            ]
+        );
+    }
+}
@@ -0,0 +1,685 @@
+//!Incremental parsing of hosts from a reader.
+//!
+//![`HostReader`](struct.HostReader.html) yields each
+//![`Host`](../host/struct.Host.html) as soon as its `host` element has been
+//!read, so scans do not have to be loaded into memory as a whole. With the
+//!`async` feature, [`AsyncHostReader`](struct.AsyncHostReader.html) does the
+//!same for a `tokio::io::AsyncRead` as a `Stream`. The first host is the
+//!exception: it is only yielded once the next host starts or the input
+//!ends, as the paths in its error locations depend on whether it is the
+//!only one.
+//!
+//!Only hosts are yielded; the other contents of the `nmaprun` element are
+//!skipped. Input that ends before the closing `nmaprun` tag, as when Nmap is
+//!still running or was interrupted, is not an error. A `host` element cut
+//!off at the end of the input is parsed as far as it goes, as
+//![`NmapResults::parse()`](../struct.NmapResults.html#method.parse) does.
+//!
+//!```
+//!# use std::path::PathBuf;
+//!# use std::fs::File;
+//!use nmap_xml_parser::stream::HostReader;
+//!# let mut nmap_xml_file = PathBuf::new();
+//!# nmap_xml_file.push(&std::env::var("CARGO_MANIFEST_DIR").unwrap());
+//!# nmap_xml_file.push("tests/test.xml");
+//!let file = File::open(nmap_xml_file).unwrap();
+//!
+//!for host in HostReader::new(file) {
+//!    let host = host.unwrap();
+//!    println!("{:?}", host.addresses().next());
+//!}
+//!```
+use roxmltree::Document;
+use std::io::Read;
+use std::ops::Range;
+
+use crate::host::Host;
+use crate::parse::ParseContext;
+use crate::{Error, Location, ParseOptions, ParseWarning};
+
+#[cfg(feature = "async")]
+pub use self::r#async::AsyncHostReader;
+
+const READ_SIZE: usize = 64 * 1024;
+
+///Reads hosts from `reader` one at a time.
+///
+///Errors from strict parsing, or from reading, are yielded in place of the
+///host they occurred in, and reading continues with the next host.
+///Locations in errors are relative to the whole input.
+#[derive(Debug)]
+pub struct HostReader<R> {
+    reader: R,
+    hosts: Hosts,
+    buf: Box<[u8]>,
+    eof: bool,
+}
+
+impl<R: Read> HostReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self::with_options(reader, &ParseOptions::default())
+    }
+
+    ///Creates a reader that parses hosts according to `options`.
+    pub fn with_options(reader: R, options: &ParseOptions) -> Self {
+        HostReader {
+            reader,
+            hosts: Hosts::new(options),
+            buf: vec![0; READ_SIZE].into_boxed_slice(),
+            eof: false,
+        }
+    }
+
+    ///Returns the problems recovered from so far when parsing in lenient
+    ///mode.
+    pub fn warnings(&self) -> std::slice::Iter<'_, ParseWarning> {
+        self.hosts.ctx.warnings().iter()
+    }
+}
+
+impl<R: Read> Iterator for HostReader<R> {
+    type Item = Result<Host, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(r) = self.hosts.next_host() {
+                return Some(r);
+            }
+            if self.eof {
+                return self.hosts.finish();
+            }
+
+            match self.reader.read(&mut self.buf) {
+                Ok(0) => self.eof = true,
+                Ok(n) => self.hosts.push(&self.buf[..n]),
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    self.eof = true;
+                    return Some(Err(e.into()));
+                }
+            }
+        }
+    }
+}
+
+///Parses the chunks produced by a [`HostSplitter`](struct.HostSplitter.html).
+#[derive(Debug)]
+struct Hosts {
+    splitter: HostSplitter,
+    ctx: ParseContext,
+    finished: bool,
+}
+
+impl Hosts {
+    fn new(options: &ParseOptions) -> Self {
+        Hosts {
+            splitter: HostSplitter::new(),
+            ctx: ParseContext::new(options),
+            finished: false,
+        }
+    }
+
+    fn push(&mut self, data: &[u8]) {
+        self.splitter.push(data);
+    }
+
+    ///Returns the next host that has been read completely, if any.
+    fn next_host(&mut self) -> Option<Result<Host, Error>> {
+        loop {
+            let chunk = match self.splitter.next_chunk()? {
+                Ok(chunk) => chunk,
+                Err(e) => return Some(Err(e)),
+            };
+            match parse_chunk(&chunk, &mut self.ctx) {
+                Ok(Some(host)) => return Some(Ok(host)),
+                Ok(None) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+
+    ///Parses the `host` element the input ended inside of, if any.
+    fn finish(&mut self) -> Option<Result<Host, Error>> {
+        if self.finished {
+            return None;
+        }
+        self.finished = true;
+
+        let chunk = self.splitter.remainder()?;
+        parse_chunk(&chunk, &mut self.ctx).transpose()
+    }
+}
+
+///Parses a single `host` element. Returns `None` if the host was skipped
+///in lenient mode.
+pub(crate) fn parse_chunk(chunk: &Chunk, ctx: &mut ParseContext) -> Result<Option<Host>, Error> {
+    let doc = Document::parse(&chunk.xml)?;
+    let node = doc.root_element();
+
+    let warnings = ctx.warnings().len();
+    let host = Host::parse(node, ctx);
+    let host = ctx.or_skip(node, host);
+    ctx.offset_warnings(warnings, &chunk.origin);
+
+    host.map_err(|mut e| {
+        if let Some(location) = e.location_mut() {
+            location.offset(&chunk.origin);
+        }
+        e
+    })
+}
+
+///The text of one `host` element and where it starts in the input.
+#[derive(Debug)]
+pub(crate) struct Chunk {
+    pub(crate) xml: String,
+    pub(crate) origin: Location,
+    ///Byte offset of the element in the input.
+    #[cfg_attr(not(feature = "rayon"), allow(dead_code))]
+    pub(crate) offset: usize,
+}
+
+///Splits Nmap XML output into the text of its `host` elements.
+///
+///This is not a full XML parser. It only tracks enough of the syntax
+///(comments, processing instructions, CDATA sections and quoted attribute
+///values) to find the start and end of each element that is a child of the
+///root element and called `host`.
+#[derive(Debug)]
+pub(crate) struct HostSplitter {
+    buf: Vec<u8>,
+    ///Number of bytes dropped from the start of `buf`.
+    dropped: usize,
+    ///Offset in `buf` of the next byte to scan.
+    pos: usize,
+    ///Line and column of `pos`, starting from 1.
+    line: u32,
+    column: u32,
+    depth: usize,
+    root_seen: bool,
+    hosts_seen: usize,
+    ///Offset in `buf` and location of the current `host` element.
+    host: Option<(usize, Location)>,
+    ///The first `host` element, held back until it is known whether it has
+    ///siblings. Element paths only index hosts that have siblings.
+    first: Option<Chunk>,
+}
+
+impl HostSplitter {
+    pub(crate) fn new() -> Self {
+        HostSplitter {
+            buf: Vec::new(),
+            dropped: 0,
+            pos: 0,
+            line: 1,
+            column: 1,
+            depth: 0,
+            root_seen: false,
+            hosts_seen: 0,
+            host: None,
+            first: None,
+        }
+    }
+
+    pub(crate) fn push(&mut self, data: &[u8]) {
+        //Drop the bytes that are no longer needed.
+        let keep = self
+            .host
+            .as_ref()
+            .map(|(start, _)| *start)
+            .unwrap_or(self.pos);
+        if keep > 0 {
+            self.buf.drain(..keep);
+            self.dropped += keep;
+            self.pos -= keep;
+            if let Some((start, _)) = &mut self.host {
+                *start -= keep;
+            }
+        }
+        self.buf.extend_from_slice(data);
+    }
+
+    ///Returns the next complete `host` element, or `None` if more input is
+    ///needed.
+    pub(crate) fn next_chunk(&mut self) -> Option<Result<Chunk, Error>> {
+        loop {
+            let start = match find(&self.buf[self.pos..], b"<") {
+                Some(i) => self.pos + i,
+                None => {
+                    self.advance(self.buf.len());
+                    return None;
+                }
+            };
+            let end = start + self.markup_len(start)?;
+            let (name, kind) = classify(&self.buf[start..end]);
+            let name = start + name.start..start + name.end;
+            self.advance(start);
+
+            let is_host = self.depth == 1 && &self.buf[name.clone()] == b"host";
+            if is_host && matches!(kind, Markup::Start | Markup::Empty) {
+                if let Some(first) = self.first.take() {
+                    return Some(Ok(first));
+                }
+            }
+
+            let origin = Location {
+                path: String::new(),
+                line: self.line,
+                column: self.column,
+            };
+            self.advance(end);
+            let name = &self.buf[name];
+
+            match kind {
+                Markup::Start | Markup::Empty if !self.root_seen => {
+                    self.root_seen = true;
+                    if name != b"nmaprun" {
+                        return Some(Err(Error::UnexpectedElement {
+                            location: Location {
+                                path: String::from_utf8_lossy(name).into_owned(),
+                                ..origin
+                            },
+                            expected: "nmaprun",
+                        }));
+                    }
+                    if kind == Markup::Start {
+                        self.depth += 1;
+                    }
+                }
+                Markup::Start | Markup::Empty => {
+                    if is_host {
+                        self.hosts_seen += 1;
+                        let origin = Location {
+                            path: format!("nmaprun/host[{}]", self.hosts_seen),
+                            ..origin
+                        };
+                        self.host = Some((start, origin));
+                    }
+                    if kind == Markup::Start {
+                        self.depth += 1;
+                    } else if is_host {
+                        if let Some(chunk) = self.take_host(end) {
+                            return Some(Ok(chunk));
+                        }
+                    }
+                }
+                Markup::End => {
+                    self.depth = self.depth.saturating_sub(1);
+                    if self.depth == 1 && name == b"host" {
+                        if let Some(chunk) = self.take_host(end) {
+                            return Some(Ok(chunk));
+                        }
+                    } else if self.depth == 0 {
+                        if let Some(first) = self.first.take() {
+                            return Some(Ok(only_host(first)));
+                        }
+                    }
+                }
+                Markup::Other => {}
+            }
+        }
+    }
+
+    ///Returns the `host` element held back or cut off at the end of the
+    ///input, if any.
+    pub(crate) fn remainder(&mut self) -> Option<Chunk> {
+        if let Some(first) = self.first.take() {
+            return Some(only_host(first));
+        }
+
+        let (start, origin) = self.host.take()?;
+        let chunk = Chunk {
+            xml: String::from_utf8_lossy(&self.buf[start..]).into_owned(),
+            origin,
+            offset: self.dropped + start,
+        };
+        if self.hosts_seen == 1 {
+            Some(only_host(chunk))
+        } else {
+            Some(chunk)
+        }
+    }
+
+    ///Returns the `host` element ending at `end`, unless it is the first
+    ///one, which is held back in `first`.
+    fn take_host(&mut self, end: usize) -> Option<Chunk> {
+        let (start, origin) = self.host.take()?;
+        let chunk = Chunk {
+            xml: String::from_utf8_lossy(&self.buf[start..end]).into_owned(),
+            origin,
+            offset: self.dropped + start,
+        };
+        if self.hosts_seen == 1 {
+            self.first = Some(chunk);
+            None
+        } else {
+            Some(chunk)
+        }
+    }
+
+    ///Returns the length of the markup starting with the `<` at `start`, or
+    ///`None` if it has not been read completely.
+    fn markup_len(&self, start: usize) -> Option<usize> {
+        let rest = &self.buf[start..];
+        let delimited = |open: &[u8], close: &[u8]| {
+            find(&rest[open.len()..], close).map(|i| open.len() + i + close.len())
+        };
+
+        if rest.starts_with(b"<!--") {
+            delimited(b"<!--", b"-->")
+        } else if rest.starts_with(b"<![CDATA[") {
+            delimited(b"<![CDATA[", b"]]>")
+        } else if rest.starts_with(b"<?") {
+            delimited(b"<?", b"?>")
+        } else if rest.len() < 9 && (b"<!--".starts_with(rest) || b"<![CDATA[".starts_with(rest)) {
+            None
+        } else {
+            //'>' may appear inside quoted attribute values.
+            let mut quote = None;
+            for (i, &b) in rest.iter().enumerate().skip(1) {
+                match (quote, b) {
+                    (None, b'"') | (None, b'\'') => quote = Some(b),
+                    (Some(q), b) if q == b => quote = None,
+                    (None, b'>') => return Some(i + 1),
+                    _ => {}
+                }
+            }
+            None
+        }
+    }
+
+    ///Moves `pos` to `to`, keeping track of the line and column.
+    fn advance(&mut self, to: usize) {
+        for &b in &self.buf[self.pos..to] {
+            if b == b'\n' {
+                self.line += 1;
+                self.column = 1;
+            } else if b & 0xC0 != 0x80 {
+                //Columns count characters, not UTF-8 continuation bytes.
+                self.column += 1;
+            }
+        }
+        self.pos = to;
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Markup {
+    Start,
+    End,
+    Empty,
+    ///Comments, processing instructions, CDATA sections and declarations.
+    Other,
+}
+
+///Returns the range of the element name in the markup `tag`, and its kind.
+fn classify(tag: &[u8]) -> (Range<usize>, Markup) {
+    let name_from = |start: usize| {
+        let len = tag[start..]
+            .iter()
+            .position(|b| b.is_ascii_whitespace() || *b == b'/' || *b == b'>')
+            .unwrap_or(tag.len() - start);
+        start..start + len
+    };
+
+    match tag.get(1) {
+        Some(b'!') | Some(b'?') => (0..0, Markup::Other),
+        Some(b'/') => (name_from(2), Markup::End),
+        _ if tag.ends_with(b"/>") => (name_from(1), Markup::Empty),
+        _ => (name_from(1), Markup::Start),
+    }
+}
+
+///Returns `chunk`, which is the only `host` element in the input, with the
+///unindexed path [`NmapResults::parse()`](../struct.NmapResults.html#method.parse)
+///would report for it.
+fn only_host(mut chunk: Chunk) -> Chunk {
+    chunk.origin.path = "nmaprun/host".to_string();
+    chunk
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    let mut from = 0;
+    while let Some(i) = haystack[from..].iter().position(|b| *b == needle[0]) {
+        let i = from + i;
+        if haystack[i..].starts_with(needle) {
+            return Some(i);
+        }
+        from = i + 1;
+    }
+    None
+}
+
+#[cfg(feature = "async")]
+mod r#async {
+    use futures_core::Stream;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+    use tokio::io::{AsyncRead, ReadBuf};
+
+    use super::{Hosts, READ_SIZE};
+    use crate::host::Host;
+    use crate::{Error, ParseOptions, ParseWarning};
+
+    ///Reads hosts from a `tokio::io::AsyncRead` one at a time.
+    ///
+    ///This is the asynchronous counterpart of
+    ///[`HostReader`](struct.HostReader.html) and behaves the same way.
+    ///
+    ///```
+    ///# #[tokio::main(flavor = "current_thread")]
+    ///# async fn main() {
+    ///# use std::path::PathBuf;
+    ///use futures_util::StreamExt;
+    ///use nmap_xml_parser::stream::AsyncHostReader;
+    ///# let mut nmap_xml_file = PathBuf::new();
+    ///# nmap_xml_file.push(&std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    ///# nmap_xml_file.push("tests/test.xml");
+    ///let file = tokio::fs::File::open(nmap_xml_file).await.unwrap();
+    ///
+    ///let mut hosts = AsyncHostReader::new(file);
+    ///while let Some(host) = hosts.next().await {
+    ///    println!("{:?}", host.unwrap().addresses().next());
+    ///}
+    ///# }
+    ///```
+    #[derive(Debug)]
+    pub struct AsyncHostReader<R> {
+        reader: R,
+        hosts: Hosts,
+        buf: Box<[u8]>,
+        eof: bool,
+    }
+
+    impl<R: AsyncRead + Unpin> AsyncHostReader<R> {
+        pub fn new(reader: R) -> Self {
+            Self::with_options(reader, &ParseOptions::default())
+        }
+
+        ///Creates a reader that parses hosts according to `options`.
+        pub fn with_options(reader: R, options: &ParseOptions) -> Self {
+            AsyncHostReader {
+                reader,
+                hosts: Hosts::new(options),
+                buf: vec![0; READ_SIZE].into_boxed_slice(),
+                eof: false,
+            }
+        }
+
+        ///Returns the problems recovered from so far when parsing in
+        ///lenient mode.
+        pub fn warnings(&self) -> std::slice::Iter<'_, ParseWarning> {
+            self.hosts.ctx.warnings().iter()
+        }
+    }
+
+    impl<R: AsyncRead + Unpin> Stream for AsyncHostReader<R> {
+        type Item = Result<Host, Error>;
+
+        fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            let this = &mut *self;
+
+            loop {
+                if let Some(r) = this.hosts.next_host() {
+                    return Poll::Ready(Some(r));
+                }
+                if this.eof {
+                    return Poll::Ready(this.hosts.finish());
+                }
+
+                let mut buf = ReadBuf::new(&mut this.buf);
+                match Pin::new(&mut this.reader).poll_read(cx, &mut buf) {
+                    Poll::Pending => return Poll::Pending,
+                    Poll::Ready(Ok(())) if buf.filled().is_empty() => this.eof = true,
+                    Poll::Ready(Ok(())) => this.hosts.push(buf.filled()),
+                    Poll::Ready(Err(e)) => {
+                        this.eof = true;
+                        return Poll::Ready(Some(Err(e.into())));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::host::Address;
+
+    const SCAN: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE nmaprun>
+<nmaprun start="1588318812" args="nmap -oX - '>host'">
+<!-- <host> -->
+<host><status state="up" reason="echo-reply" reason_ttl="53"/>
+<address addr="192.0.2.1" addrtype="ipv4"/>
+<!-- </host> -->
+<hostscript><script id="x" output="/>"/></hostscript>
+</host>
+<host><status state="sleeping" reason="echo-reply" reason_ttl="53"/>
+<address addr="192.0.2.2" addrtype="ipv4"/>
+</host>
+<host><address addr="192.0.2.3" addrtype="ipv4"/></host>
+<runstats><finished time="1588318912"/></runstats>
+</nmaprun>
+"#;
+
+    fn read_in_pieces(xml: &str, size: usize, options: &ParseOptions) -> Vec<Result<Host, Error>> {
+        let mut hosts = Hosts::new(options);
+        let mut out = Vec::new();
+        for piece in xml.as_bytes().chunks(size) {
+            hosts.push(piece);
+            while let Some(r) = hosts.next_host() {
+                out.push(r);
+            }
+        }
+        out.extend(hosts.finish());
+        out
+    }
+
+    fn address(host: &Host) -> Address {
+        host.addresses().next().unwrap().clone()
+    }
+
+    #[test]
+    fn splits_hosts_across_reads() {
+        for size in &[1, 7, 64, SCAN.len()] {
+            let results = read_in_pieces(SCAN, *size, &ParseOptions::default());
+
+            assert_eq!(results.len(), 3, "read size {}", size);
+            let host = results[0].as_ref().unwrap();
+            assert_eq!(address(host), Address::IpAddr("192.0.2.1".parse().unwrap()));
+            assert_eq!(host.scripts().next().unwrap().output, "/>");
+            assert!(results[1].is_err());
+            assert!(results[2].is_err());
+        }
+    }
+
+    #[test]
+    fn error_locations_are_relative_to_input() {
+        let results = read_in_pieces(SCAN, 5, &ParseOptions::default());
+        let err = results[1].as_ref().unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "invalid value \"sleeping\" for `state` attribute at nmaprun/host[2]/status (line 10, column 15)"
+        );
+    }
+
+    #[test]
+    fn lenient_warnings_are_relative_to_input() {
+        let options = ParseOptions {
+            strict: false,
+            ..Default::default()
+        };
+        let mut reader = HostReader::with_options(SCAN.as_bytes(), &options);
+        let hosts = reader.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+
+        assert_eq!(hosts.len(), 2);
+        let paths = reader
+            .warnings()
+            .map(|w| w.location.path.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, vec!["nmaprun/host[2]/status", "nmaprun/host[3]"]);
+        assert_eq!(
+            reader.warnings().next().unwrap().to_string(),
+            "invalid value \"sleeping\" for `state` attribute at nmaprun/host[2]/status (line 10, column 15)"
+        );
+    }
+
+    #[test]
+    fn single_host_locations_are_not_indexed() {
+        let start = SCAN.find("<host><status state=\"sleeping\"").unwrap();
+        let end = SCAN.find("<host><address").unwrap();
+        let xml = format!(
+            "{}{}</nmaprun>",
+            &SCAN[..SCAN.find("<host><status").unwrap()],
+            &SCAN[start..end]
+        );
+
+        for size in &[1, 16, xml.len()] {
+            let results = read_in_pieces(&xml, *size, &ParseOptions::default());
+            assert_eq!(results.len(), 1);
+            assert_eq!(
+                results[0].as_ref().unwrap_err().to_string(),
+                crate::NmapResults::parse(&xml).unwrap_err().to_string()
+            );
+        }
+    }
+
+    #[test]
+    fn unexpected_root_element() {
+        let err = HostReader::new("<nmap><host/></nmap>".as_bytes())
+            .next()
+            .unwrap()
+            .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "expected `nmaprun` element at nmap (line 1, column 1)"
+        );
+    }
+
+    #[test]
+    fn input_ending_between_hosts() {
+        let end = SCAN.find("<host><address").unwrap();
+        let results = read_in_pieces(&SCAN[..end], 16, &ParseOptions::default());
+
+        assert_eq!(results.len(), 2);
+    }
+
+    #[test]
+    fn input_ending_inside_host() {
+        let end = SCAN.find("<hostscript>").unwrap();
+        let results = read_in_pieces(&SCAN[..end], 16, &ParseOptions::default());
+
+        //Like `NmapResults::parse()`, the host is parsed as far as it goes.
+        assert_eq!(results.len(), 1);
+        let host = results[0].as_ref().unwrap();
+        assert_eq!(address(host), Address::IpAddr("192.0.2.1".parse().unwrap()));
+        assert_eq!(host.scripts().len(), 0);
+
+        let results = crate::NmapResults::parse(&SCAN[..end]).unwrap();
+        assert_eq!(results.hosts().count(), 1);
+    }
+}
@@ -0,0 +1,287 @@
+//!Vulnerability findings reported by NSE scripts.
+//!
+//!Findings are extracted from three kinds of script output:
+//!
+//!* Scripts in the `vuln` category, which report each vulnerability as a
+//!  table in the layout of Nmap's `vulns` library, with `title`, `state`,
+//!  `ids`, `scores` and `refs` entries.
+//!* The `vulners` script, which lists known vulnerabilities for each CPE
+//!  detected on a port.
+//!* The `vulscan` script, which only produces text output of the form
+//!  `[ID] title`.
+//!
+//!```
+//!# use std::path::PathBuf;
+//!# use std::fs;
+//!use nmap_xml_parser::NmapResults;
+//!use nmap_xml_parser::vulns::VulnState;
+//!# let mut nmap_xml_file = PathBuf::new();
+//!# nmap_xml_file.push(&std::env::var("CARGO_MANIFEST_DIR").unwrap());
+//!# nmap_xml_file.push("tests/vulns.xml");
+//!let content = fs::read_to_string(nmap_xml_file).unwrap();
+//!let results = NmapResults::parse(&content).unwrap();
+//!
+//!for finding in results.findings() {
+//!    if finding.state == VulnState::Vulnerable {
+//!        println!("{} {:?}", finding.script_id, finding.cve_ids);
+//!    }
+//!}
+//!```
+use crate::host::{Host, Script, ScriptElement, ScriptValue};
+use crate::port::Port;
+
+///Whether a target is affected by a vulnerability.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VulnState {
+    ///Confirmed vulnerable, including Nmap's `VULNERABLE (Exploitable)` and
+    ///`VULNERABLE (DoS)` states.
+    Vulnerable,
+    ///Probably vulnerable. Findings from `vulners` and `vulscan` are always
+    ///in this state as they are based on the detected version only.
+    LikelyVulnerable,
+    NotVulnerable,
+    ///Any other state, e.g. `UNKNOWN (unable to test)`.
+    Unknown,
+}
+
+impl VulnState {
+    fn from_nmap(s: &str) -> Self {
+        let s = s.trim();
+        if s.starts_with("NOT VULNERABLE") {
+            VulnState::NotVulnerable
+        } else if s.starts_with("LIKELY VULNERABLE") {
+            VulnState::LikelyVulnerable
+        } else if s.starts_with("VULNERABLE") {
+            VulnState::Vulnerable
+        } else {
+            VulnState::Unknown
+        }
+    }
+}
+
+///A vulnerability reported by a script.
+#[derive(Clone, Debug)]
+pub struct Finding<'a> {
+    pub host: &'a Host,
+    ///The port the script ran against. `None` for host scripts.
+    pub port: Option<&'a Port>,
+    pub script_id: &'a str,
+    ///Identifier of the vulnerability, e.g. `CVE-2017-0143` or
+    ///`EDB-ID:40888`. Identifiers from `vulscan` databases other than CVE
+    ///are prefixed with the database name, e.g. `exploitdb:34133`, as their
+    ///numbering overlaps. Findings without any identifier are given one
+    ///made of the script name and their position in its output, e.g.
+    ///`http-vuln-cve2017-5638:1`.
+    pub id: String,
+    pub title: Option<String>,
+    pub state: VulnState,
+    pub cve_ids: Vec<String>,
+    ///CVSS base score. CVSSv3 is preferred when a script reports both.
+    pub cvss: Option<f32>,
+    pub references: Vec<String>,
+}
+
+impl<'a> Finding<'a> {
+    ///Extracts the findings reported by `script`, which ran against `port`
+    ///on `host`.
+    pub fn from_script(host: &'a Host, port: Option<&'a Port>, script: &'a Script) -> Vec<Self> {
+        let new = |id: String| Finding {
+            host,
+            port,
+            script_id: &script.id,
+            id,
+            title: None,
+            state: VulnState::Unknown,
+            cve_ids: Vec::new(),
+            cvss: None,
+            references: Vec::new(),
+        };
+
+        match script.id.as_str() {
+            "vulners" => vulners(&script.elements, new),
+            "vulscan" => vulscan(&script.output, new),
+            _ => vulns_tables(&script.id, &script.elements, new),
+        }
+    }
+
+    ///Extracts all findings on `host`, from both host and port scripts.
+    pub fn from_host(host: &'a Host) -> Vec<Self> {
+        let mut results = Vec::new();
+        for script in host.scripts() {
+            results.extend(Finding::from_script(host, None, script));
+        }
+        for port in host.port_info.ports() {
+            for script in &port.scripts {
+                results.extend(Finding::from_script(host, Some(port), script));
+            }
+        }
+        results
+    }
+}
+
+fn text(value: &ScriptValue, key: &str) -> Option<String> {
+    value
+        .get(key)
+        .and_then(|v| v.as_str())
+        .map(|s| s.trim().to_string())
+}
+
+fn list(value: &ScriptValue, key: &str) -> Vec<String> {
+    value
+        .get(key)
+        .and_then(|v| v.as_table())
+        .unwrap_or(&[])
+        .iter()
+        .filter_map(|e| e.value.as_str())
+        .map(|s| s.trim().to_string())
+        .collect()
+}
+
+fn vulns_tables<'a, F>(script_id: &str, elements: &[ScriptElement], new: F) -> Vec<Finding<'a>>
+where
+    F: Fn(String) -> Finding<'a>,
+{
+    let mut results = Vec::new();
+
+    for (i, e) in elements.iter().enumerate() {
+        let table = &e.value;
+        let state = match text(table, "state") {
+            Some(s) => VulnState::from_nmap(&s),
+            None => continue,
+        };
+        let title = text(table, "title");
+
+        let cve_ids = list(table, "ids")
+            .iter()
+            .filter_map(|id| id.strip_prefix("CVE:"))
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>();
+        let id = e
+            .key
+            .clone()
+            .or_else(|| cve_ids.first().cloned())
+            .or_else(|| title.clone())
+            .unwrap_or_else(|| format!("{}:{}", script_id, i));
+
+        let cvss = ["CVSSv3", "CVSSv2"]
+            .iter()
+            .filter_map(|k| table.get("scores").and_then(|s| s.get(k)))
+            .filter_map(|v| v.as_str())
+            .find_map(|s| s.trim().parse().ok());
+
+        results.push(Finding {
+            title,
+            state,
+            cve_ids,
+            cvss,
+            references: list(table, "refs"),
+            ..new(id)
+        });
+    }
+
+    results
+}
+
+fn vulners<'a, F>(elements: &[ScriptElement], new: F) -> Vec<Finding<'a>>
+where
+    F: Fn(String) -> Finding<'a>,
+{
+    let mut results = Vec::new();
+
+    //One table per CPE, containing one table per vulnerability.
+    for cpe in elements {
+        for e in cpe.value.as_table().unwrap_or(&[]) {
+            let id = match text(&e.value, "id") {
+                Some(id) => id,
+                None => continue,
+            };
+            let kind = text(&e.value, "type").unwrap_or_else(|| "cve".to_string());
+
+            results.push(Finding {
+                state: VulnState::LikelyVulnerable,
+                cve_ids: if kind == "cve" {
+                    vec![id.clone()]
+                } else {
+                    Vec::new()
+                },
+                cvss: text(&e.value, "cvss").and_then(|s| s.parse().ok()),
+                references: vec![format!("https://vulners.com/{}/{}", kind, id)],
+                ..new(id)
+            });
+        }
+    }
+
+    results
+}
+
+fn vulscan<'a, F>(output: &str, new: F) -> Vec<Finding<'a>>
+where
+    F: Fn(String) -> Finding<'a>,
+{
+    let mut results = Vec::new();
+    //Each database is introduced by a line naming it, either by its file,
+    //e.g. `exploitdb.csv:`, or by its title, e.g. `VulDB - https://vuldb.com:`.
+    let mut database: Option<String> = None;
+
+    for line in output.lines() {
+        let line = line.trim_start_matches(['|', '_', ' ']).trim_end();
+        if let Some(file) = line.strip_suffix(':') {
+            database = file
+                .split(" - ")
+                .next()
+                .and_then(|name| name.split('.').next())
+                .map(|name| name.trim().to_ascii_lowercase())
+                .filter(|name| !name.is_empty());
+            continue;
+        }
+        let (id, title) = match line.strip_prefix('[').and_then(|l| l.split_once(']')) {
+            Some((id, title)) => (id.trim(), title.trim()),
+            None => continue,
+        };
+
+        results.push(Finding {
+            title: Some(title.to_string()),
+            state: VulnState::LikelyVulnerable,
+            cve_ids: if id.starts_with("CVE-") {
+                vec![id.to_string()]
+            } else {
+                Vec::new()
+            },
+            ..new(match &database {
+                Some(db) if !id.starts_with("CVE-") => format!("{}:{}", db, id),
+                _ => id.to_string(),
+            })
+        });
+    }
+
+    results
+}
+
+#[cfg(test)]
+mod test {
+    use crate::NmapResults;
+
+    #[test]
+    fn finding_ids() {
+        let xml = r#"<nmaprun start="1588318812">
+<host><status state="up" reason="user-set" reason_ttl="0"/>
+<address addr="192.0.2.1" addrtype="ipv4"/>
+<ports><port protocol="tcp" portid="80"><state state="open" reason="syn-ack" reason_ttl="64"/>
+<script id="vulscan" output="VulDB - https://vuldb.com:&#xa;[1234] a&#xa;&#xa;MITRE CVE - https://cve.mitre.org:&#xa;[CVE-2014-0226] b&#xa;&#xa;osvdb.csv:&#xa;[1234] c&#xa;"/>
+<script id="http-vuln-test" output=""><table><elem key="state">VULNERABLE</elem></table></script>
+</port></ports>
+</host>
+</nmaprun>"#;
+        let results = NmapResults::parse(xml).unwrap();
+        let ids = results.findings().map(|f| f.id).collect::<Vec<_>>();
+        assert_eq!(
+            ids,
+            vec![
+                "vuldb:1234",
+                "CVE-2014-0226",
+                "osvdb:1234",
+                "http-vuln-test:0"
+            ]
+        );
+    }
+}
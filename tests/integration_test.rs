@@ -1,42 +1,92 @@
 #[macro_use]
 extern crate lazy_static;
 
-use nmap_xml_parser::{host, port, NmapResults};
+use nmap_xml_parser::args;
+use nmap_xml_parser::collection::ScanCollection;
+use nmap_xml_parser::correlate;
+use nmap_xml_parser::report::{html, markdown, normal};
+use nmap_xml_parser::rescan;
+use nmap_xml_parser::scripts::{self, Decoded, Decoders, ScriptDecoder};
+use nmap_xml_parser::stream::HostReader;
+use nmap_xml_parser::vulns::VulnState;
+use nmap_xml_parser::{cpe, export, host, port, NmapResults, NmapResultsRef, ParseOptions};
 use std::fs;
 use std::path::PathBuf;
 
 lazy_static! {
     static ref NMAP_TEST_XML: NmapResults = {
         let mut path = PathBuf::new();
-        path.push(&std::env::var("CARGO_MANIFEST_DIR").unwrap());
+        path.push(std::env::var("CARGO_MANIFEST_DIR").unwrap());
         path.push("tests/test.xml");
         let content = fs::read_to_string(path).unwrap();
         NmapResults::parse(&content).unwrap()
     };
     static ref NMAP_ISSUE_ONE: NmapResults = {
         let mut path = PathBuf::new();
-        path.push(&std::env::var("CARGO_MANIFEST_DIR").unwrap());
+        path.push(std::env::var("CARGO_MANIFEST_DIR").unwrap());
         path.push("tests/issue_1.xml");
         let content = fs::read_to_string(path).unwrap();
         NmapResults::parse(&content).unwrap()
     };
     static ref NMAP_HOST_DOWN: NmapResults = {
         let mut path = PathBuf::new();
-        path.push(&std::env::var("CARGO_MANIFEST_DIR").unwrap());
+        path.push(std::env::var("CARGO_MANIFEST_DIR").unwrap());
         path.push("tests/host-down.xml");
         let content = fs::read_to_string(path).unwrap();
         NmapResults::parse(&content).unwrap()
     };
     static ref NMAP_INCOMPLETE_SCAN: NmapResults = {
         let mut path = PathBuf::new();
-        path.push(&std::env::var("CARGO_MANIFEST_DIR").unwrap());
+        path.push(std::env::var("CARGO_MANIFEST_DIR").unwrap());
         path.push("tests/incomplete_scan.xml");
         let content = fs::read_to_string(path).unwrap();
         NmapResults::parse(&content).unwrap()
     };
+    static ref NMAP_SERVICE_VERSION: NmapResults = {
+        let mut path = PathBuf::new();
+        path.push(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+        path.push("tests/scanme-version.xml");
+        let content = fs::read_to_string(path).unwrap();
+        NmapResults::parse(&content).unwrap()
+    };
+    static ref NMAP_EXTRAPORTS: NmapResults = {
+        let mut path = PathBuf::new();
+        path.push(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+        path.push("tests/extraports.xml");
+        let content = fs::read_to_string(path).unwrap();
+        NmapResults::parse(&content).unwrap()
+    };
+    static ref NMAP_SCRIPTS: NmapResults = {
+        let mut path = PathBuf::new();
+        path.push(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+        path.push("tests/scripts.xml");
+        let content = fs::read_to_string(path).unwrap();
+        NmapResults::parse(&content).unwrap()
+    };
+    static ref NMAP_VULNS: NmapResults = {
+        let mut path = PathBuf::new();
+        path.push(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+        path.push("tests/vulns.xml");
+        let content = fs::read_to_string(path).unwrap();
+        NmapResults::parse(&content).unwrap()
+    };
+    static ref NMAP_IPV6: NmapResults = {
+        let mut path = PathBuf::new();
+        path.push(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+        path.push("tests/ipv6.xml");
+        let content = fs::read_to_string(path).unwrap();
+        NmapResults::parse(&content).unwrap()
+    };
+    static ref NMAP_IPV4_DUAL: NmapResults = {
+        let mut path = PathBuf::new();
+        path.push(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+        path.push("tests/ipv4-dual.xml");
+        let content = fs::read_to_string(path).unwrap();
+        NmapResults::parse(&content).unwrap()
+    };
 }
 
-fn vectors_eq<T: PartialEq>(a: &Vec<T>, b: &Vec<T>) -> bool {
+fn vectors_eq<T: PartialEq>(a: &[T], b: &[T]) -> bool {
     let matching = a.iter().zip(b.iter()).filter(|&(a, b)| a == b).count();
     matching == a.len() || matching == b.len()
 }
@@ -78,7 +128,7 @@ fn host_ip_address() {
     let ip_addr = host.addresses().next().unwrap();
     match ip_addr {
         host::Address::IpAddr(s) => assert_eq!(s, &ip),
-        host::Address::MacAddr(_) => assert!(false),
+        host::Address::MacAddr(_) => panic!(),
     }
 }
 
@@ -108,83 +158,47 @@ fn host_hostnames() {
     expected.push(&h1);
     expected.push(&h2);
 
-    assert!(!(host.host_names().count() == 0));
-    assert!(vectors_eq(&host.host_names().collect(), &expected));
+    assert!((host.host_names().count() != 0));
+    assert!(vectors_eq(
+        &host.host_names().collect::<Vec<_>>(),
+        &expected
+    ));
 }
 
 #[test]
 fn host_portinfo_ports() {
     let host = NMAP_TEST_XML.hosts().next().unwrap();
 
-    let mut expected = Vec::new();
-
-    let p1 = port::Port {
-        protocol: port::PortProtocol::Tcp,
-        port_number: 22,
-        status: port::PortStatus {
-            state: port::PortState::Open,
-            reason: "syn-ack".to_string(),
-            reason_ttl: 53,
-        },
-        service_info: Some(port::ServiceInfo {
-            name: "ssh".to_string(),
-            method: port::ServiceMethod::Table,
-            confidence_level: 3,
-        }),
-    };
+    let expected = vec![
+        (22, 53, "ssh"),
+        (80, 52, "http"),
+        (9929, 53, "nping-echo"),
+        (31337, 52, "Elite"),
+    ];
 
-    let p2 = port::Port {
-        protocol: port::PortProtocol::Tcp,
-        port_number: 80,
-        status: port::PortStatus {
-            state: port::PortState::Open,
-            reason: "syn-ack".to_string(),
-            reason_ttl: 52,
-        },
-        service_info: Some(port::ServiceInfo {
-            name: "http".to_string(),
-            method: port::ServiceMethod::Table,
-            confidence_level: 3,
-        }),
-    };
-
-    let p3 = port::Port {
-        protocol: port::PortProtocol::Tcp,
-        port_number: 9929,
-        status: port::PortStatus {
-            state: port::PortState::Open,
-            reason: "syn-ack".to_string(),
-            reason_ttl: 53,
-        },
-        service_info: Some(port::ServiceInfo {
-            name: "nping-echo".to_string(),
-            method: port::ServiceMethod::Table,
-            confidence_level: 3,
-        }),
-    };
+    let ports = host.port_info.ports().collect::<Vec<_>>();
+    assert_eq!(ports.len(), expected.len());
+    for (port, (port_number, reason_ttl, name)) in ports.into_iter().zip(expected) {
+        assert_eq!(port.protocol, port::PortProtocol::Tcp);
+        assert_eq!(port.port_number, port_number);
+        assert_eq!(port.status.state, port::PortState::Open);
+        assert_eq!(port.status.reason, "syn-ack");
+        assert_eq!(port.status.reason_ttl, reason_ttl);
+        assert!(port.status.unrecognized().is_empty());
 
-    let p4 = port::Port {
-        protocol: port::PortProtocol::Tcp,
-        port_number: 31337,
-        status: port::PortStatus {
-            state: port::PortState::Open,
-            reason: "syn-ack".to_string(),
-            reason_ttl: 52,
-        },
-        service_info: Some(port::ServiceInfo {
-            name: "Elite".to_string(),
-            method: port::ServiceMethod::Table,
-            confidence_level: 3,
-        }),
-    };
-
-    expected.push(&p1);
-    expected.push(&p2);
-    expected.push(&p3);
-    expected.push(&p4);
-
-    assert!(!(host.port_info.ports().count() == 0));
-    assert!(vectors_eq(&host.port_info.ports().collect(), &expected));
+        let service = port.service_info.as_ref().unwrap();
+        assert_eq!(service.name, name);
+        assert!(service.product.is_none());
+        assert!(service.version.is_none());
+        assert!(service.extrainfo.is_none());
+        assert_eq!(service.method, port::ServiceMethod::Table);
+        assert_eq!(service.confidence_level, 3);
+        assert!(service.cpe.is_empty());
+        assert!(service.unparsed_cpe.is_empty());
+        assert!(service.unrecognized().is_empty());
+        assert!(port.scripts.is_empty());
+        assert!(port.unrecognized().is_empty());
+    }
 }
 
 #[test]
@@ -201,13 +215,13 @@ fn test_issue_one() {
     println!("{:?}", ip_addr);
     match ip_addr {
         host::Address::IpAddr(s) => assert_eq!(s, &ip),
-        host::Address::MacAddr(_) => assert!(false),
+        host::Address::MacAddr(_) => panic!(),
     }
 
     let mac_addr = addresses.next().unwrap();
     println!("{:?}", mac_addr);
     match mac_addr {
-        host::Address::IpAddr(_) => assert!(false),
+        host::Address::IpAddr(_) => panic!(),
         host::Address::MacAddr(s) => assert_eq!(s, &mac),
     }
 }
@@ -233,3 +247,957 @@ fn test_host_down() {
         assert_eq!(host.status.state, HostState::Down);
     }
 }
+
+#[test]
+fn service_cpe() {
+    let (_, ssh) = NMAP_SERVICE_VERSION
+        .iter_ports()
+        .find(|(_, p)| p.port_number == 22)
+        .unwrap();
+    let service = ssh.service_info.as_ref().unwrap();
+
+    assert_eq!(service.product.as_deref(), Some("OpenSSH"));
+    assert_eq!(
+        service.version.as_deref(),
+        Some("6.6.1p1 Ubuntu 2ubuntu2.13")
+    );
+    assert_eq!(
+        service.extrainfo.as_deref(),
+        Some("Ubuntu Linux; protocol 2.0")
+    );
+    assert_eq!(service.cpe.len(), 2);
+    assert_eq!(service.cpe[0].part, cpe::CpePart::Application);
+    assert_eq!(service.cpe[0].product.as_str(), Some("openssh"));
+    assert_eq!(
+        service.cpe[0].to_cpe23(),
+        "cpe:2.3:a:openbsd:openssh:6.6.1p1:*:*:*:*:*:*:*"
+    );
+    assert_eq!(service.cpe[1].to_string(), "cpe:/o:linux:linux_kernel");
+}
+
+#[test]
+fn host_os_matches() {
+    let host = NMAP_SERVICE_VERSION.hosts().next().unwrap();
+    let matches = host.os_matches().collect::<Vec<_>>();
+
+    assert_eq!(matches.len(), 2);
+    assert_eq!(matches[0].name, "Linux 4.4");
+    assert_eq!(matches[0].accuracy, 95);
+    assert_eq!(matches[1].classes.len(), 2);
+
+    let class = &matches[1].classes[1];
+    assert_eq!(class.device_type.as_deref(), Some("general purpose"));
+    assert_eq!(class.family, "Linux");
+    assert_eq!(class.generation.as_deref(), Some("3.X"));
+    assert_eq!(class.cpe[0].to_string(), "cpe:/o:linux:linux_kernel:3");
+}
+
+#[test]
+fn unrecognized_not_preserved_by_default() {
+    assert!(NMAP_TEST_XML.unrecognized().is_empty());
+
+    let host = NMAP_TEST_XML.hosts().next().unwrap();
+    assert!(host.unrecognized().is_empty());
+}
+
+#[test]
+fn preserve_unrecognized() {
+    let mut path = PathBuf::new();
+    path.push(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    path.push("tests/scanme-version.xml");
+    let content = fs::read_to_string(path).unwrap();
+    let options = ParseOptions {
+        preserve_unknown: true,
+        ..Default::default()
+    };
+    let results = NmapResults::parse_with_options(&content, &options).unwrap();
+
+    let unrecognized = results.unrecognized();
+    assert_eq!(unrecognized.attribute("version"), Some("7.80"));
+    assert!(unrecognized.attribute("start").is_none());
+    assert_eq!(
+        unrecognized.element("verbose").unwrap().to_string(),
+        r#"<verbose level="0"/>"#
+    );
+
+    let host = results.hosts().next().unwrap();
+    let trace = host.unrecognized().element("trace").unwrap();
+    assert_eq!(trace.children.len(), 2);
+    assert_eq!(trace.children[1].attribute("host"), Some("scanme.nmap.org"));
+    assert!(host.unrecognized().element("status").is_none());
+
+    assert!(host.port_info.unrecognized().is_empty());
+
+    let http = host.port_info.ports().nth(1).unwrap();
+    assert!(http.unrecognized().is_empty());
+    assert_eq!(http.scripts.len(), 2);
+    assert_eq!(http.scripts[1].id, "http-title");
+    assert!(http.status.unrecognized().is_empty());
+    assert!(http.scripts[1].unrecognized().is_empty());
+
+    let ssh = host.port_info.ports().next().unwrap();
+    let service = ssh.service_info.as_ref().unwrap();
+    assert_eq!(service.unrecognized().attribute("ostype"), Some("Linux"));
+    assert!(service.unrecognized().element("cpe").is_none());
+
+    let os_match = host.os_matches().next().unwrap();
+    assert_eq!(os_match.unrecognized().attribute("line"), Some("66123"));
+    assert!(os_match.classes[0].unrecognized().is_empty());
+    assert!(host.status.unrecognized().is_empty());
+}
+
+fn port_script<'a>(results: &'a NmapResults, port: u16, id: &str) -> &'a host::Script {
+    let (_, p) = results
+        .iter_ports()
+        .find(|(_, p)| p.port_number == port)
+        .unwrap();
+    p.scripts.iter().find(|s| s.id == id).unwrap()
+}
+
+#[test]
+fn decode_ssl_cert() {
+    let script = port_script(&NMAP_SCRIPTS, 443, "ssl-cert");
+    let cert = scripts::SslCert::decode(script).unwrap();
+
+    assert_eq!(cert.subject.common_name(), Some("win7.corp.example"));
+    assert_eq!(cert.issuer.get("organizationName"), Some("Example Corp"));
+    assert_eq!(
+        cert.subject_alt_names,
+        vec!["DNS:win7.corp.example", "DNS:www.corp.example"]
+    );
+    assert_eq!(cert.not_before.as_deref(), Some("2020-01-06T14:30:12"));
+    assert_eq!(cert.not_after.as_deref(), Some("2022-01-05T14:30:12"));
+    assert_eq!(cert.key_type.as_deref(), Some("rsa"));
+    assert_eq!(cert.key_bits, Some(2048));
+    assert_eq!(
+        cert.sha1.as_deref(),
+        Some("2d0f88e47a435c1f7e0b1dd26a21f4e10f1a6d20")
+    );
+    assert!(cert.pem.unwrap().starts_with("-----BEGIN CERTIFICATE-----"));
+}
+
+#[test]
+fn decode_ssh_hostkey() {
+    let script = port_script(&NMAP_SERVICE_VERSION, 22, "ssh-hostkey");
+    let keys = scripts::SshHostKey::decode_all(script).unwrap();
+
+    assert_eq!(keys.len(), 2);
+    assert_eq!(keys[1].key_type, "ssh-rsa");
+    assert_eq!(keys[1].bits, Some(2048));
+    assert_eq!(keys[1].fingerprint, "203d2d44622ab05a9db5b30514c2a6b2");
+    assert!(
+        scripts::SshHostKey::decode_all(port_script(&NMAP_SERVICE_VERSION, 80, "http-title"))
+            .is_none()
+    );
+}
+
+#[test]
+fn decode_http_title() {
+    let title = scripts::HttpTitle::decode(port_script(&NMAP_SCRIPTS, 443, "http-title")).unwrap();
+    assert_eq!(title.title.as_deref(), Some("IIS7"));
+    assert!(title.redirect_url.is_none());
+
+    let redirect =
+        scripts::HttpTitle::decode(port_script(&NMAP_SCRIPTS, 80, "http-title")).unwrap();
+    assert!(redirect.title.is_none());
+    assert_eq!(
+        redirect.redirect_url.as_deref(),
+        Some("https://win7.corp.example/")
+    );
+}
+
+#[test]
+fn decode_smb_os_discovery() {
+    let host = NMAP_SCRIPTS.hosts().next().unwrap();
+    let script = host.scripts().next().unwrap();
+    let smb = scripts::SmbOsDiscovery::decode(script).unwrap();
+
+    assert_eq!(
+        smb.os.as_deref(),
+        Some("Windows 7 Professional 7601 Service Pack 1")
+    );
+    assert_eq!(smb.netbios_computer_name.as_deref(), Some("WIN7"));
+    assert_eq!(smb.workgroup.as_deref(), Some("CORP"));
+    assert_eq!(smb.domain.as_deref(), Some("CORP"));
+    assert_eq!(smb.domain_dns.as_deref(), Some("corp.example"));
+    assert_eq!(smb.fqdn.as_deref(), Some("win7.corp.example"));
+
+    let cpe = smb.cpe.unwrap();
+    assert_eq!(cpe.part, cpe::CpePart::OperatingSystem);
+    assert_eq!(cpe.product.as_str(), Some("windows_7"));
+    assert_eq!(
+        cpe.to_string(),
+        "cpe:/o:microsoft:windows_7::sp1:professional"
+    );
+}
+
+#[test]
+fn decoder_registry() {
+    struct ServerHeader;
+
+    impl ScriptDecoder for ServerHeader {
+        fn script_id(&self) -> &str {
+            "http-server-header"
+        }
+
+        fn decode(&self, script: &host::Script) -> Option<Decoded> {
+            let header = script.elements.first()?.value.as_str()?.to_string();
+            Some(Decoded::Custom(Box::new(header)))
+        }
+    }
+
+    let mut decoders = Decoders::new();
+    decoders.register(ServerHeader);
+
+    let host = NMAP_SERVICE_VERSION.hosts().next().unwrap();
+    let http = host
+        .port_info
+        .ports()
+        .find(|p| p.port_number == 80)
+        .unwrap();
+    let decoded = http
+        .scripts
+        .iter()
+        .filter_map(|s| decoders.decode(s))
+        .collect::<Vec<_>>();
+
+    assert_eq!(decoded.len(), 2);
+    match &decoded[0] {
+        Decoded::Custom(c) => assert_eq!(
+            c.downcast_ref::<String>().map(|s| s.as_str()),
+            Some("Apache/2.4.7 (Ubuntu)")
+        ),
+        d => panic!("unexpected decode: {:?}", d),
+    }
+    match &decoded[1] {
+        Decoded::HttpTitle(t) => assert_eq!(t.title.as_deref(), Some("Go ahead and ScanMe!")),
+        d => panic!("unexpected decode: {:?}", d),
+    }
+
+    assert!(Decoders::empty().decode(&http.scripts[1]).is_none());
+}
+
+#[test]
+fn vulns_findings() {
+    let findings = NMAP_VULNS.findings().collect::<Vec<_>>();
+    let ids = findings.iter().map(|f| f.id.as_str()).collect::<Vec<_>>();
+    assert_eq!(
+        ids,
+        vec![
+            "CVE-2017-0143",
+            "CVE-2015-5600",
+            "CVE-2016-10009",
+            "EDB-ID:40888",
+            "CVE-2017-5638",
+            "CVE-2007-6750",
+            "CVE-2014-0226",
+            "CVE-2014-0231",
+            "exploitdb:34133",
+        ]
+    );
+
+    let ms17 = &findings[0];
+    assert!(ms17.port.is_none());
+    assert_eq!(ms17.script_id, "smb-vuln-ms17-010");
+    assert_eq!(ms17.state, VulnState::Vulnerable);
+    assert_eq!(ms17.cve_ids, vec!["CVE-2017-0143"]);
+    assert_eq!(ms17.cvss, Some(9.3));
+    assert_eq!(ms17.references.len(), 3);
+    assert_eq!(
+        ms17.host.addresses().next(),
+        Some(&host::Address::IpAddr("192.168.59.141".parse().unwrap()))
+    );
+
+    let struts = &findings[4];
+    assert_eq!(struts.port.unwrap().port_number, 80);
+    assert_eq!(struts.state, VulnState::LikelyVulnerable);
+    assert_eq!(
+        struts.title.as_deref(),
+        Some("Apache Struts Remote Code Execution Vulnerability")
+    );
+    assert!(struts.cvss.is_none());
+
+    assert_eq!(findings[5].state, VulnState::NotVulnerable);
+}
+
+#[test]
+fn vulners_findings() {
+    let findings = NMAP_VULNS
+        .findings()
+        .filter(|f| f.script_id == "vulners")
+        .collect::<Vec<_>>();
+    assert_eq!(findings.len(), 3);
+
+    assert_eq!(findings[0].port.unwrap().port_number, 22);
+    assert_eq!(findings[0].state, VulnState::LikelyVulnerable);
+    assert_eq!(findings[0].cvss, Some(8.5));
+    assert_eq!(findings[0].cve_ids, vec!["CVE-2015-5600"]);
+    assert_eq!(
+        findings[0].references,
+        vec!["https://vulners.com/cve/CVE-2015-5600"]
+    );
+
+    assert!(findings[2].cve_ids.is_empty());
+    assert_eq!(
+        findings[2].references,
+        vec!["https://vulners.com/exploitdb/EDB-ID:40888"]
+    );
+}
+
+#[test]
+fn vulscan_findings() {
+    let findings = NMAP_VULNS
+        .findings()
+        .filter(|f| f.script_id == "vulscan")
+        .collect::<Vec<_>>();
+    assert_eq!(findings.len(), 3);
+    assert_eq!(findings[0].cve_ids, vec!["CVE-2014-0226"]);
+    assert!(findings[2].cve_ids.is_empty());
+    assert_eq!(
+        findings[2].title.as_deref(),
+        Some("Apache 2.4.7 mod_status - Scoreboard Handling Race Condition")
+    );
+}
+
+#[test]
+fn no_findings_without_vuln_scripts() {
+    assert_eq!(NMAP_SERVICE_VERSION.findings().count(), 0);
+    assert_eq!(NMAP_SCRIPTS.findings().count(), 0);
+}
+
+#[test]
+fn sarif_export() {
+    let sarif = export::to_sarif(&NMAP_VULNS);
+    assert_eq!(sarif["version"], "2.1.0");
+
+    let run = &sarif["runs"][0];
+    assert_eq!(run["tool"]["driver"]["name"], "nmap");
+
+    let results = run["results"].as_array().unwrap();
+    let rule_ids = results
+        .iter()
+        .map(|r| r["ruleId"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        rule_ids,
+        vec![
+            "open-port",
+            "open-port",
+            "open-port",
+            "CVE-2017-0143",
+            "CVE-2015-5600",
+            "CVE-2016-10009",
+            "EDB-ID:40888",
+            "CVE-2017-5638",
+            "CVE-2014-0226",
+            "CVE-2014-0231",
+            "exploitdb:34133",
+        ]
+    );
+
+    assert_eq!(results[0]["level"], "note");
+    assert_eq!(results[0]["message"]["text"], "Port 22/tcp is open (ssh)");
+    assert_eq!(
+        results[0]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+        "//192.168.59.141:22"
+    );
+    assert_eq!(
+        results[0]["locations"][0]["logicalLocations"][0]["name"],
+        "192.168.59.141:22/tcp"
+    );
+
+    assert_eq!(results[3]["level"], "error");
+    assert_eq!(results[3]["properties"]["cves"][0], "CVE-2017-0143");
+    assert_eq!(results[7]["level"], "warning");
+
+    let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+    let ms17 = rules.iter().find(|r| r["id"] == "CVE-2017-0143").unwrap();
+    assert_eq!(ms17["properties"]["security-severity"], "9.3");
+    assert_eq!(
+        ms17["helpUri"],
+        "https://technet.microsoft.com/en-us/library/security/ms17-010.aspx"
+    );
+}
+
+#[test]
+fn json_lines_export() {
+    let mut out = Vec::new();
+    export::write_json_lines(&NMAP_SCRIPTS, &mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+
+    let events = out
+        .lines()
+        .map(|l| serde_json::from_str::<serde_json::Value>(l).unwrap())
+        .collect::<Vec<_>>();
+    let kinds = events
+        .iter()
+        .map(|e| e["event"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        vec!["host", "script", "port", "script", "port", "script", "script", "port"]
+    );
+
+    assert_eq!(events[0]["address"], "192.168.59.140");
+    assert_eq!(events[0]["addresses"][1], "00:0C:29:3A:5B:11");
+    assert_eq!(events[0]["hostnames"][0], "win7.corp.example");
+    assert_eq!(events[0]["state"], "up");
+
+    assert_eq!(events[1]["script_id"], "smb-os-discovery");
+    assert!(events[1]["port"].is_null());
+
+    assert_eq!(events[2]["protocol"], "tcp");
+    assert_eq!(events[2]["port"], 80);
+    assert_eq!(events[2]["state"], "open");
+    assert_eq!(events[2]["service"], "http");
+
+    assert_eq!(events[3]["port"], 80);
+    assert_eq!(events[3]["script_id"], "http-title");
+}
+
+#[test]
+fn scan_stats() {
+    let stats = NMAP_SERVICE_VERSION.stats();
+
+    assert_eq!(stats.hosts_by_state[&host::HostState::Up], 1);
+    assert_eq!(stats.ports_by_state[&port::PortState::Open], 4);
+    assert_eq!(stats.ports_by_state[&port::PortState::Filtered], 1);
+    assert_eq!(stats.ports_by_state[&port::PortState::Closed], 995);
+    assert_eq!(stats.ports_by_protocol[&port::PortProtocol::Tcp], 1000);
+    assert_eq!(stats.top_services(1), vec![("http", 1)]);
+    assert_eq!(
+        stats.top_products(2),
+        vec![("Apache httpd", 1), ("Nping echo", 1)]
+    );
+    assert_eq!(stats.hosts_with_most_open_ports(1)[0].1, 4);
+    assert_eq!(
+        stats.duration,
+        Some(NMAP_SERVICE_VERSION.scan_end_time.unwrap() - NMAP_SERVICE_VERSION.scan_start_time)
+    );
+}
+
+#[test]
+fn extra_ports_and_times() {
+    let host = NMAP_SERVICE_VERSION.hosts().next().unwrap();
+
+    let extra = host.port_info.extra_ports().collect::<Vec<_>>();
+    assert_eq!(extra.len(), 1);
+    assert_eq!(extra[0].state, port::PortState::Closed);
+    assert_eq!(extra[0].count, 995);
+    assert_eq!(extra[0].reasons[0].reason, "resets");
+
+    let times = host.times.as_ref().unwrap();
+    assert_eq!(times.srtt, 153588);
+    assert_eq!(times.timeout, 200000);
+}
+
+fn golden(name: &str) -> String {
+    let mut path = PathBuf::new();
+    path.push(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    path.push("tests/golden");
+    path.push(name);
+    fs::read_to_string(path).unwrap()
+}
+
+///Returns the golden `-oN` file `name` with each of `differences` applied.
+///A difference pairs text of the file that `normal::render` does not
+///reproduce, as documented in `report::normal`, with what it renders
+///instead. Each must occur exactly once, so the rest of the file is
+///compared in full.
+fn golden_normal(name: &str, differences: &[(&str, &str)]) -> String {
+    let mut nmap = golden(name);
+    for (text, rendered) in differences {
+        assert_eq!(nmap.matches(text).count(), 1, "{:?} in {}", text, name);
+        nmap = nmap.replacen(text, rendered, 1);
+    }
+    nmap
+}
+
+#[test]
+fn normal_output_golden() {
+    assert_eq!(
+        normal::render(&NMAP_SERVICE_VERSION),
+        golden_normal(
+            "scanme-version.nmap",
+            &[
+                (
+                    "# Nmap 7.80 scan initiated Sat Jun 13 10:21:07 2020 as: nmap -A -oA scanme-version scanme.nmap.org\n",
+                    "",
+                ),
+                (
+                    "Network Distance: 12 hops
+Service Info: OS: Linux; CPE: cpe:/o:linux:linux_kernel
+
+TRACEROUTE (using port 80/tcp)
+HOP RTT       ADDRESS
+1   ... 10
+11  154.12 ms 173.230.159.17
+12  153.91 ms scanme.nmap.org (45.33.32.156)
+",
+                    "",
+                ),
+                (
+                    "OS and Service detection performed. Please report any incorrect results at https://nmap.org/submit/ .
+# Nmap done at Sat Jun 13 10:21:41 2020 -- 1 IP address (1 host up) scanned in 34.12 seconds
+",
+                    "",
+                ),
+            ]
+        )
+    );
+    assert_eq!(
+        normal::render(&NMAP_SCRIPTS),
+        golden_normal(
+            "scripts.nmap",
+            &[
+                (
+                    "# Nmap 7.80 scan initiated Mon Jun 15 09:12:44 2020 as: nmap -sV -p 80,443,445 --script ssl-cert,http-title,smb-os-discovery -oX scripts.xml 192.168.59.140\n",
+                    "",
+                ),
+                (
+                    "MAC Address: 00:0C:29:3A:5B:11 (VMware)
+Service Info: Host: WIN7; OS: Windows; CPE: cpe:/o:microsoft:windows
+",
+                    "MAC Address: 00:0C:29:3A:5B:11\n",
+                ),
+                (
+                    "Service detection performed. Please report any incorrect results at https://nmap.org/submit/ .
+# Nmap done at Mon Jun 15 09:13:11 2020 -- 1 IP address (1 host up) scanned in 27.41 seconds
+",
+                    "",
+                ),
+            ]
+        )
+    );
+}
+
+#[test]
+fn normal_output_extraports_only() {
+    assert_eq!(
+        normal::render(&NMAP_EXTRAPORTS),
+        golden_normal(
+            "extraports.nmap",
+            &[
+                (
+                    "# Nmap 7.80 scan initiated Fri Jun 19 11:02:16 2020 as: nmap -oX extraports.xml 192.168.59.150\n",
+                    "",
+                ),
+                (
+                    "MAC Address: 00:0C:29:7E:21:04 (VMware)\n",
+                    "MAC Address: 00:0C:29:7E:21:04\n",
+                ),
+                (
+                    "# Nmap done at Fri Jun 19 11:02:21 2020 -- 1 IP address (1 host up) scanned in 5.21 seconds\n",
+                    "",
+                ),
+            ]
+        )
+    );
+}
+
+#[test]
+fn normal_output_skips_down_hosts() {
+    assert_eq!(normal::render(&NMAP_HOST_DOWN), "");
+
+    let host = NMAP_HOST_DOWN.hosts().next().unwrap();
+    assert!(normal::render_host(host).ends_with("[host down]\n"));
+}
+
+#[test]
+fn markdown_golden() {
+    assert_eq!(
+        markdown::render(&NMAP_SERVICE_VERSION),
+        golden("scanme-version.md")
+    );
+}
+
+#[test]
+fn html_report() {
+    let page = html::render(&NMAP_SCRIPTS);
+
+    assert!(page.starts_with("<!DOCTYPE html>"));
+    assert!(page.contains("Hosts: 1 up, 0 down."));
+    assert!(page.contains(
+        "<tr><td><a href=\"#host-1\">192.168.59.140</a></td><td>win7.corp.example</td><td>up</td><td>3</td></tr>"
+    ));
+    assert!(page.contains("<section id=\"host-1\">"));
+    assert!(
+        page.contains("<details><summary>443/tcp http-title</summary><pre>IIS7</pre></details>")
+    );
+    assert!(page.contains("<details><summary>smb-os-discovery</summary>"));
+    assert!(page.contains("table.sortable"));
+    assert!(!page.contains("<link") && !page.contains("src=\""));
+}
+
+#[test]
+fn html_escapes_output() {
+    let page = html::render(&NMAP_VULNS);
+    assert!(page.contains("cvename.cgi?name=CVE-2017-0143"));
+    assert!(!page.contains("<ssl"));
+
+    let host = NMAP_SCRIPTS.hosts().next().unwrap();
+    let section = html::render_host(host, "a\"b");
+    assert!(section.starts_with("<section id=\"a&quot;b\">"));
+}
+
+#[test]
+fn host_reader_matches_parse() {
+    for (name, results) in &[
+        ("tests/test.xml", &*NMAP_TEST_XML),
+        ("tests/scanme-version.xml", &*NMAP_SERVICE_VERSION),
+        ("tests/incomplete_scan.xml", &*NMAP_INCOMPLETE_SCAN),
+    ] {
+        let mut path = PathBuf::new();
+        path.push(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+        path.push(name);
+        let file = fs::File::open(path).unwrap();
+
+        let streamed = HostReader::new(file)
+            .map(|h| format!("{:?}", h.unwrap()))
+            .collect::<Vec<_>>();
+        let parsed = results
+            .hosts()
+            .map(|h| format!("{:?}", h))
+            .collect::<Vec<_>>();
+        assert_eq!(streamed, parsed, "{}", name);
+    }
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn async_host_reader_matches_parse() {
+    use futures_util::StreamExt;
+    use nmap_xml_parser::stream::AsyncHostReader;
+
+    let mut path = PathBuf::new();
+    path.push(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    path.push("tests/test.xml");
+    let file = tokio::fs::File::open(path).await.unwrap();
+
+    let streamed = AsyncHostReader::new(file)
+        .map(|h| format!("{:?}", h.unwrap()))
+        .collect::<Vec<_>>()
+        .await;
+    let parsed = NMAP_TEST_XML
+        .hosts()
+        .map(|h| format!("{:?}", h))
+        .collect::<Vec<_>>();
+    assert_eq!(streamed, parsed);
+}
+
+#[test]
+fn borrowed_parse_matches_parse() {
+    for (name, results) in &[
+        ("tests/test.xml", &*NMAP_TEST_XML),
+        ("tests/scanme-version.xml", &*NMAP_SERVICE_VERSION),
+        ("tests/scripts.xml", &*NMAP_SCRIPTS),
+        ("tests/vulns.xml", &*NMAP_VULNS),
+    ] {
+        let mut path = PathBuf::new();
+        path.push(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+        path.push(name);
+        let content = fs::read_to_string(path).unwrap();
+
+        let borrowed = NmapResultsRef::parse(&content).unwrap();
+        assert_eq!(
+            format!("{:?}", borrowed.to_owned()),
+            format!("{:?}", results),
+            "{}",
+            name
+        );
+    }
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn par_parse_matches_parse() {
+    for (name, results) in &[
+        ("tests/test.xml", &*NMAP_TEST_XML),
+        ("tests/host-down.xml", &*NMAP_HOST_DOWN),
+        ("tests/incomplete_scan.xml", &*NMAP_INCOMPLETE_SCAN),
+        ("tests/scripts.xml", &*NMAP_SCRIPTS),
+    ] {
+        let mut path = PathBuf::new();
+        path.push(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+        path.push(name);
+        let content = fs::read_to_string(path).unwrap();
+
+        let parallel = NmapResults::par_parse(&content).unwrap();
+        assert_eq!(
+            format!("{:?}", parallel),
+            format!("{:?}", results),
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn from_path() {
+    let mut path = PathBuf::new();
+    path.push(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    path.push("tests/scanme-version.xml");
+
+    let results = NmapResults::from_path(path).unwrap();
+    assert_eq!(
+        format!("{:?}", results),
+        format!("{:?}", *NMAP_SERVICE_VERSION)
+    );
+
+    let err = NmapResults::from_path("tests/does-not-exist.xml").unwrap_err();
+    assert!(matches!(err, nmap_xml_parser::Error::Io(_)));
+}
+
+#[test]
+fn from_path_compressed() {
+    let compressed = &[
+        ("tests/scanme-version.xml.gz", cfg!(feature = "gzip")),
+        ("tests/scanme-version.xml.zst", cfg!(feature = "zstd")),
+        ("tests/scanme-version.xml.xz", cfg!(feature = "xz")),
+    ];
+
+    for (name, enabled) in compressed {
+        let mut path = PathBuf::new();
+        path.push(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+        path.push(name);
+
+        match NmapResults::from_path(path) {
+            Ok(results) => {
+                assert!(enabled, "{}", name);
+                assert_eq!(
+                    format!("{:?}", results),
+                    format!("{:?}", *NMAP_SERVICE_VERSION)
+                );
+            }
+            Err(nmap_xml_parser::Error::Io(_)) => assert!(!enabled, "{}", name),
+            Err(e) => panic!("{}: {}", name, e),
+        }
+    }
+}
+
+#[test]
+fn scan_collection_from_dir() {
+    let manifest_dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    let dir = std::env::temp_dir().join(format!("nmap_xml_parser-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::copy(manifest_dir.join("tests/test.xml"), dir.join("a.xml")).unwrap();
+    fs::copy(manifest_dir.join("tests/host-down.xml"), dir.join("b.xml")).unwrap();
+    fs::write(dir.join("c.xml"), "<nmaprun></nmaprun>").unwrap();
+    fs::write(dir.join("notes.txt"), "not a scan").unwrap();
+
+    let scans = ScanCollection::from_dir(&dir).unwrap();
+    let lenient = ParseOptions {
+        strict: false,
+        ..Default::default()
+    };
+    let lenient = ScanCollection::from_dir_with_options(&dir, &lenient).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    let paths = scans.scans().map(|s| s.path.clone()).collect::<Vec<_>>();
+    assert_eq!(paths, vec![dir.join("a.xml"), dir.join("b.xml")]);
+
+    let failures = scans.failures().collect::<Vec<_>>();
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].path, dir.join("c.xml"));
+    assert_eq!(
+        failures[0].error.to_string(),
+        "missing `start` attribute at nmaprun (line 1, column 1)"
+    );
+
+    let expected = NMAP_TEST_XML.hosts().count() + NMAP_HOST_DOWN.hosts().count();
+    assert_eq!(scans.hosts().len(), expected);
+    assert!(scans
+        .hosts()
+        .take(NMAP_TEST_XML.hosts().count())
+        .all(|h| h.source() == Some(dir.join("a.xml").as_path())));
+    assert_eq!(
+        scans.hosts().last().unwrap().source(),
+        Some(dir.join("b.xml").as_path())
+    );
+    assert_eq!(
+        scans.iter_ports().len(),
+        NMAP_TEST_XML.iter_ports().len() + NMAP_HOST_DOWN.iter_ports().len()
+    );
+
+    //`start` is required in lenient mode too.
+    assert_eq!(lenient.scans().len(), 2);
+    assert_eq!(lenient.failures().len(), 1);
+}
+
+#[test]
+fn scan_collection_from_glob() {
+    let mut pattern = PathBuf::new();
+    pattern.push(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    pattern.push("tests/[sv]*.xml");
+
+    let scans = ScanCollection::from_glob(pattern.to_str().unwrap()).unwrap();
+    let names = scans
+        .scans()
+        .map(|s| s.path.file_name().unwrap().to_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec!["scanme-version.xml", "scripts.xml", "vulns.xml"]
+    );
+    assert_eq!(scans.failures().len(), 0);
+    assert_eq!(scans.findings().len(), NMAP_VULNS.findings().len());
+    assert_eq!(NMAP_VULNS.hosts().next().unwrap().source(), None);
+    assert!(scans.scans().all(|s| s.results.unrecognized().is_empty()));
+
+    let options = ParseOptions {
+        preserve_unknown: true,
+        ..Default::default()
+    };
+    let scans =
+        ScanCollection::from_glob_with_options(pattern.to_str().unwrap(), &options).unwrap();
+    assert_eq!(scans.scans().len(), 3);
+    assert!(scans.scans().all(|s| !s.results.unrecognized().is_empty()));
+
+    assert!(ScanCollection::from_glob("tests/[.xml").is_err());
+}
+
+#[test]
+fn scan_options() {
+    assert_eq!(
+        NMAP_TEST_XML.args.as_deref(),
+        Some("nmap -sS -oA test scanme.nmap.org")
+    );
+
+    let options = NMAP_SCRIPTS.scan_options().unwrap();
+    assert_eq!(options.program, "nmap");
+    assert_eq!(options.option("-p"), Some("80,443,445"));
+    assert_eq!(
+        options.option("--script"),
+        Some("ssl-cert,http-title,smb-os-discovery")
+    );
+    assert_eq!(options.targets, vec!["192.168.59.140"]);
+    assert_eq!(options.to_string(), NMAP_SCRIPTS.args.as_deref().unwrap());
+    assert!(options.version_detection());
+    assert!(!options.uses_script_category("intrusive"));
+
+    let options = NMAP_SERVICE_VERSION.scan_options().unwrap();
+    assert!(options.os_detection());
+    assert!(options.uses_script_category("default"));
+    assert_eq!(options.outputs()[0].format, args::OutputFormat::All);
+
+    let options = NMAP_TEST_XML.scan_options().unwrap();
+    assert_eq!(options.scan_types(), vec![args::ScanType::Syn]);
+    assert_eq!(options.timing_template(), None);
+    assert!(!options.skips_host_discovery());
+}
+
+#[test]
+fn rescan_command() {
+    let targets = rescan::ip_targets(NMAP_HOST_DOWN.hosts().skip(2));
+    let original = NMAP_HOST_DOWN.scan_options().unwrap();
+
+    let command = rescan::command(&original, targets, Some("T:1234".to_string()));
+    assert_eq!(
+        command.to_string(),
+        "nmap -v -p T:1234 192.168.59.234 192.168.59.235"
+    );
+}
+
+#[test]
+fn scan_info() {
+    let info = NMAP_SCRIPTS.scan_info().collect::<Vec<_>>();
+    assert_eq!(info.len(), 1);
+    assert_eq!(info[0].scan_type, args::ScanType::Syn);
+    assert_eq!(info[0].protocol, port::PortProtocol::Tcp);
+    assert_eq!(info[0].services.to_string(), "80,443,445");
+
+    let top = NMAP_SERVICE_VERSION.scanned_ports(&port::PortProtocol::Tcp);
+    assert_eq!(top.len(), 1000);
+    assert!(top.contains(8443));
+    assert!(!NMAP_SCRIPTS
+        .scanned_ports(&port::PortProtocol::Tcp)
+        .contains(8443));
+    assert!(NMAP_SCRIPTS
+        .scanned_ports(&port::PortProtocol::Udp)
+        .is_empty());
+}
+
+#[test]
+fn coverage() {
+    let (_, coverage) = NMAP_SERVICE_VERSION.coverage().next().unwrap();
+    assert_eq!(coverage.len(), 1);
+    let tcp = &coverage[0];
+    assert_eq!(tcp.scanned.len(), 1000);
+    assert_eq!(tcp.total(&port::PortState::Closed), 995);
+    assert_eq!(tcp.ports(&port::PortState::Closed).len(), 995);
+    assert_eq!(tcp.state(22), Some(&port::PortState::Open));
+    assert_eq!(tcp.state(8443), Some(&port::PortState::Closed));
+    assert!(tcp.unknown.is_empty());
+
+    let (host, coverage) = NMAP_SCRIPTS.coverage().next().unwrap();
+    assert_eq!(coverage[0].state(8443), None);
+    assert_eq!(host.coverage(NMAP_SCRIPTS.scan_info()), coverage);
+}
+
+#[test]
+fn ipv6_scan() {
+    let addresses = NMAP_IPV6
+        .hosts()
+        .map(|h| h.addresses().next().unwrap().clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        addresses,
+        vec![
+            host::Address::IpAddr("2001:db8::10".parse().unwrap()),
+            host::Address::IpAddr("2001:db8::20".parse().unwrap()),
+            host::Address::IpAddr("fe80::a00:27ff:fe4e:66a1".parse().unwrap()),
+        ]
+    );
+    let zones = NMAP_IPV6.hosts().map(|h| h.zone()).collect::<Vec<_>>();
+    assert_eq!(zones, vec![None, None, Some("eth0")]);
+    let scoped = NMAP_IPV6.hosts().nth(2).unwrap();
+    assert_eq!(
+        scoped.key().unwrap().ip,
+        "fe80::a00:27ff:fe4e:66a1"
+            .parse::<std::net::IpAddr>()
+            .unwrap()
+    );
+    assert_eq!(
+        rescan::ip_targets(NMAP_IPV6.hosts()),
+        vec![
+            "2001:db8::10",
+            "2001:db8::20",
+            "fe80::a00:27ff:fe4e:66a1%eth0"
+        ]
+    );
+    assert_eq!(
+        NMAP_IPV6.scan_options().unwrap().targets,
+        vec![
+            "dual.example",
+            "2001:db8::20",
+            "fe80::a00:27ff:fe4e:66a1%eth0"
+        ]
+    );
+}
+
+#[test]
+fn correlate_dual_stack() {
+    let hosts = NMAP_IPV4_DUAL.hosts().chain(NMAP_IPV6.hosts());
+    let machines = correlate::correlate(hosts);
+
+    let summary = machines
+        .iter()
+        .map(|m| {
+            let mut ips = m.ipv4().iter().map(|ip| ip.to_string()).collect::<Vec<_>>();
+            ips.extend(m.ipv6().iter().map(|ip| ip.to_string()));
+            (ips.join(" "), m.is_dual_stack())
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        vec![
+            ("192.0.2.10 2001:db8::10".to_string(), true),
+            ("192.0.2.30 fe80::a00:27ff:fe4e:66a1".to_string(), true),
+            ("192.0.2.40".to_string(), false),
+            ("2001:db8::20".to_string(), false),
+        ]
+    );
+    assert_eq!(machines[0].names(), vec!["dual.example."]);
+    assert_eq!(machines[0].mac_addresses(), vec!["08:00:27:aa:bb:01"]);
+    assert_eq!(machines[0].hosts().len(), 2);
+}